@@ -1,22 +1,29 @@
 use crate::{
     dubin::{
         calculate_dubin_path_candidates, calculate_georeferenced_dubin_path_candidates,
-        GeographicDubinPath,
+        critical_start_angles, GeographicDubinPath,
     },
-    helpers::binary_search,
-    Aircraft, HumanPresenceCategory, Location, LocationMap, RiskClassification, SurfaceType,
-    UsageType,
+    expand_bounds,
+    helpers::{binary_search, regula_falsi},
+    risk_score, Aircraft, AltitudeRaster, ElevationModel, Location, LocationMap, Obstruction,
+    RescueProximity, RiskClassification, RiskFactor, RiskFacts, RiskReason, RiskRules, Runway,
+    SeaState, SurfaceType, UsageType, WindConditions, SCREEN_HEIGHT,
 };
+use chrono::{DateTime, FixedOffset, Local};
 use geo::{
-    prelude::{EuclideanDistance, HaversineDestination},
+    prelude::{Bearing, EuclideanDistance, HaversineDestination, HaversineDistance},
     rotate::RotatePoint,
-    LineString, MultiLineString, MultiPolygon, Point, Polygon,
+    Coordinate, LineString, MultiLineString, MultiPolygon, Point, Polygon, Rect,
 };
 use geo_booleanop::boolean::BooleanOp;
 use geojson::{feature::Id, Feature, FeatureCollection, GeoJson};
 use serde::{Deserialize, Serialize};
 use serde_json::{to_value, Map};
-use std::{cmp::Ordering, collections::HashMap, f64::consts::FRAC_PI_2};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    f64::consts::{FRAC_PI_2, PI},
+};
 use strum::IntoEnumIterator;
 use svg::{
     node::element::{path::Data, Path, Rectangle, Text},
@@ -26,12 +33,59 @@ use uom::si::{
     angle::{degree, radian},
     f64::{Angle, Length},
     length::{foot, meter},
+    velocity::meter_per_second,
 };
 use wasm_bindgen::prelude::*;
 
-pub struct AircraftRangeProfile([Point<f64>; 18]);
+/// Water narrower than this (in meters) leaves no room for error when touching down
+const DITCHING_UNSAFE_WIDTH: f64 = 25.0;
+const DITCHING_RISKY_WIDTH: f64 = 60.0;
+/// Distance to the shore (in meters) beyond which swimming becomes a serious risk
+const DITCHING_UNSAFE_SHORE_DISTANCE: f64 = 500.0;
+const DITCHING_RISKY_SHORE_DISTANCE: f64 = 150.0;
+/// Spacing (in meters) of the touchdown points considered along runways which are longer than required
+const TOUCHDOWN_RESOLUTION: f64 = 50.0;
+
+/// Number of evenly spaced headings checked alongside the critical ones when searching for the worst approach
+const HEADING_SAMPLES: usize = 12;
+/// Iterations spent on refining the worst heading found among the candidates
+const HEADING_REFINEMENTS: usize = 8;
+/// Number of distances along each ray at which the outermost reachable point is searched before refining it
+const RANGE_SAMPLES: usize = 32;
+/// Spacing (in meters) at which the terrain is sampled along glide paths
+const TERRAIN_RESOLUTION: f64 = 25.0;
+/// Spacing (in meters) at which the reachable locations are determined along routes
+const ROUTE_RESOLUTION: f64 = 100.0;
+/// Precision (in meters) to which heights above locations are compared when sharing range profiles.
+/// Altitudes and elevations are converted from feet, so equal heights rarely come out exactly the same.
+const HEIGHT_RESOLUTION: f64 = 0.001;
+
+/// Outline of the area from which the threshold of a runway can be reached with the assumed initial heading.
+/// Points are ordered counterclockwise in a local geometric coordinate system that has the threshold at its origin,
+/// beginning at the approach and with the landing direction pointing along the positive y-axis.
+/// Alongside each point the distance (in meters) flown through the air on the way to the threshold is kept.
+pub struct AircraftRangeProfile(Vec<Point<f64>>, Vec<f64>);
+/// Same as `AircraftRangeProfile` but taking the runway into account
 #[derive(Clone)]
-pub struct LocationRangeProfile([Point<f64>; 18]);
+pub struct LocationRangeProfile(Vec<Point<f64>>);
+
+/// Range profiles at a height above the ground, shared by all locations at the same elevation
+struct RangeProfiles {
+    /// `None` if the profile depends on the direction of each runway
+    shared: Option<AircraftRangeProfile>,
+    /// Profiles with the worst and best initial heading, if requested
+    envelopes: Option<[AircraftRangeProfile; 2]>,
+}
+
+/// Work which does not depend on the altitude and is thus shared by all altitudes of a batch
+#[derive(Default)]
+struct ReachabilityCache {
+    /// Risk assessment and landing directions by location
+    locations: HashMap<String, (RiskAssessment, Vec<Runway>)>,
+    /// Range profiles by height above the ground. With MSL altitudes, locations of different altitudes share them
+    /// wherever they lie at the same height below the aircraft.
+    profiles: HashMap<i64, RangeProfiles>,
+}
 
 #[wasm_bindgen(inspectable)]
 #[derive(Serialize, Deserialize, Clone, Copy)]
@@ -49,15 +103,92 @@ pub struct Preferences {
     pub event_location_classification: RiskClassification,
     #[wasm_bindgen(js_name = "denselyCrowdedClassification")]
     pub densely_crowded_classification: RiskClassification,
+
+    /// Age of a survey (in days) beyond which the location is considered risky, staleness is ignored if absent
+    #[wasm_bindgen(js_name = "riskySurveyAge")]
+    #[serde(default)]
+    pub risky_survey_age: Option<u32>,
+    /// Age of a survey (in days) beyond which the location is considered unsafe, staleness is ignored if absent
+    #[wasm_bindgen(js_name = "unsafeSurveyAge")]
+    #[serde(default)]
+    pub unsafe_survey_age: Option<u32>,
+
+    /// Number of directions in which the range around a threshold is calculated
+    #[wasm_bindgen(js_name = "profileRays")]
+    #[serde(default = "Preferences::default_profile_rays")]
+    pub profile_rays: usize,
+    /// Initial heading assumed at every point of the range around a threshold
+    #[wasm_bindgen(js_name = "profileMode")]
+    #[serde(default = "Preferences::default_profile_mode")]
+    pub profile_mode: ProfileMode,
+    /// Heading (in degrees) flown at every point when using `ProfileMode::Heading`
+    #[wasm_bindgen(js_name = "profileHeading")]
+    #[serde(default)]
+    pub profile_heading: f64,
+}
+
+/// Initial heading which is assumed when calculating from where a threshold can be reached
+#[wasm_bindgen]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum ProfileMode {
+    /// Reachable regardless of the heading
+    WorstCase = "WorstCase",
+    /// Reachable when already pointed in the most favourable direction
+    BestCase = "BestCase",
+    /// Reachable when flying the heading given in the preferences
+    Heading = "Heading",
+}
+
+/// Initial heading at every point of an aircraft range profile
+#[derive(Clone, Copy)]
+enum InitialHeading {
+    Worst,
+    Best,
+    /// Heading in radians within the local coordinate system of the profile
+    Fixed(f64),
+}
+
+/// Datum to which an altitude passed into the calculator refers
+#[wasm_bindgen]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum AltitudeReference {
+    /// Above mean sea level
+    MSL = "MSL",
+    /// Above the ground, which is assumed to be level with the elevation of each location
+    AGL = "AGL",
 }
 
 #[wasm_bindgen(inspectable)]
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct RiskAssessment {
     pub overall: RiskClassification,
+    /// Risk between 0 and 100 which falls into the score range of the overall classification
+    pub score: f64,
     pub surface: RiskClassification,
     pub headroom: RiskClassification,
     pub humans: RiskClassification,
+    pub obstacles: RiskClassification,
+    pub crops: RiskClassification,
+    pub staleness: RiskClassification,
+    pub ditching: Option<DitchingAssessment>,
+    /// Explanations for every factor that is not considered safe
+    #[wasm_bindgen(skip)]
+    pub reasons: Vec<RiskReason>,
+}
+
+#[wasm_bindgen(inspectable)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct DitchingAssessment {
+    pub overall: RiskClassification,
+    pub width: RiskClassification,
+    pub shore: RiskClassification,
+    #[wasm_bindgen(js_name = "seaState")]
+    pub sea_state: RiskClassification,
+    pub rescue: RiskClassification,
+    /// Recommended heading in degrees for touching down on the water
+    pub heading: f64,
 }
 
 // TODO Move into its own file and put calculation logic inside
@@ -71,266 +202,638 @@ pub struct TakeoffProfile {
     svg: String,
 }
 
+/// Initial heading assumed at every point along a route when determining the altitude required to reach a location
+#[wasm_bindgen]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum RouteHeading {
+    /// Reachable regardless of the heading
+    WorstCase = "WorstCase",
+    /// Reachable when flying along the route
+    AlongTrack = "AlongTrack",
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MinimumAltitude {
+    /// Distance travelled along the route (in meters)
+    distance: f64,
+    /// Lowest altitude (in meters) from which a location can be reached, `None` if it exceeds the ceiling
+    altitude: Option<f64>,
+    /// Location that can be reached from the lowest altitude
+    location: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MinimumAltitudeProfile {
+    /// Minimum altitudes at evenly spaced points along the route
+    points: Vec<MinimumAltitude>,
+    /// Rendering of the minimum altitude profile
+    svg: String,
+}
+
 #[wasm_bindgen]
-pub struct Calculator;
+pub struct Calculator {
+    rules: RiskRules,
+    terrain: Option<ElevationModel>,
+}
+
+impl AircraftRangeProfile {
+    /// Largest distance (in meters) from a threshold at which a location may still be reached
+    fn reach(&self) -> f64 {
+        let origin = Point::new(0.0, 0.0);
+
+        self.0
+            .iter()
+            .map(|point| point.euclidean_distance(&origin))
+            .fold(0.0, f64::max)
+    }
+}
 
 impl Calculator {
-    fn aircraft_range_profile(
+    /// Range profile shared by all runways, `None` if it depends on the direction of each runway
+    fn shared_range_profile(
+        &self,
+        preferences: &Preferences,
+        aircraft: &Aircraft,
+        altitude: f64,
+        mode: ProfileMode,
+    ) -> Option<AircraftRangeProfile> {
+        let initial_heading = match mode {
+            ProfileMode::WorstCase => InitialHeading::Worst,
+            ProfileMode::BestCase => InitialHeading::Best,
+            _ => return None,
+        };
+
+        Some(self.aircraft_range_profile(preferences, aircraft, altitude, initial_heading))
+    }
+
+    /// Range profile for a landing in the direction of the runway while flying the heading from the preferences
+    fn runway_heading_range_profile(
         &self,
         preferences: &Preferences,
         aircraft: &Aircraft,
         altitude: f64,
+        runway: &Runway,
     ) -> AircraftRangeProfile {
-        let maximum_range = aircraft.glide.ratio() * altitude * 2.0;
-        let circle_radius = aircraft.glide.turn_radius(preferences.bank);
-        let circle_origin = Point::new(-circle_radius, 0.0);
+        // The landing direction points along the positive y-axis of the profile
+        let heading = (preferences.profile_heading - runway.bearing() + 90.0).to_radians();
+
+        self.aircraft_range_profile(
+            preferences,
+            aircraft,
+            altitude,
+            InitialHeading::Fixed(heading),
+        )
+    }
 
-        let point_on_circle = |angle: f64| {
-            circle_origin + Point::new(circle_radius * angle.cos(), circle_radius * angle.sin())
+    fn aircraft_range_profile(
+        &self,
+        preferences: &Preferences,
+        aircraft: &Aircraft,
+        altitude: f64,
+        initial_heading: InitialHeading,
+    ) -> AircraftRangeProfile {
+        // No path can be shorter than the straight line
+        let maximum_range = aircraft.glide.ratio() * altitude;
+        let profile_path = |position: Point<f64>, limit: f64| {
+            self.profile_path(preferences, aircraft, position, initial_heading, limit)
         };
 
+        // Cast rays from the threshold, starting at the approach and going counterclockwise
+        let rays = preferences.profile_rays.max(3);
+        let step = maximum_range / RANGE_SAMPLES as f64;
+        let (points, distances) = (0..rays)
+            .map(|i| -FRAC_PI_2 + 2.0 * PI * i as f64 / rays as f64)
+            .map(|angle| {
+                let direction = Point::new(angle.cos(), angle.sin());
+                let excess = |range: f64| profile_path(direction * range, altitude).0 - altitude;
+
+                // Close to the threshold a turn may be required, so points further out can be reachable
+                // while closer ones are not. Search inwards for the outermost reachable sample before narrowing it down.
+                let mut outer = None;
+                let mut inner = None;
+                for range in (1..=RANGE_SAMPLES).rev().map(|i| i as f64 * step) {
+                    let sample = (range, excess(range));
+
+                    if sample.1 <= 0.0 {
+                        inner = Some(sample);
+                        break;
+                    }
+
+                    outer = Some(sample);
+                }
+
+                let distance = match (inner, outer) {
+                    (Some(inner), Some(outer)) => {
+                        regula_falsi(inner, outer, preferences.epsilon, excess)
+                    }
+                    (Some(inner), None) => inner.0,
+                    (None, _) => {
+                        binary_search(0.0, step, preferences.epsilon, |range| excess(range) <= 0.0)
+                    }
+                };
+                let point = direction * distance;
+
+                (point, profile_path(point, f64::INFINITY).1)
+            })
+            .unzip();
+
+        AircraftRangeProfile(points, distances)
+    }
+
+    /// Height lost and distance flown on the shortest path from a point with the assumed initial heading to the
+    /// threshold, given in the local coordinate system of a range profile. The search for the worst heading stops
+    /// as soon as a heading loses more height than the limit, which is all that matters for points out of reach.
+    fn profile_path(
+        &self,
+        preferences: &Preferences,
+        aircraft: &Aircraft,
+        position: Point<f64>,
+        initial_heading: InitialHeading,
+        limit: f64,
+    ) -> (f64, f64) {
+        let radius = Length::new::<meter>(aircraft.glide.turn_radius(preferences.bank));
         let origin = Point::new(0.0, 0.0);
         let origin_angle = Angle::new::<degree>(90.0);
-        let radius = Length::new::<meter>(circle_radius);
 
-        let mut points = [Point::new(0.0, 0.0); 18];
-
-        // Beyond 180º the "worst" angle can no longer be derived by looking at the approach circle on the side of the aircraft location only.
-        // As the angle grows, it becomes viable to choose the other side for the approach and thus the actual worst angle is shallower.
-        // Calculating this is a bit more involved, thus we will ignore this detail and only calculate angles up to 180º. Beyond that,
-        // a straight connection line will be drawn. While this is only an approximation, it errors on the side of caution and thus presents
-        // an acceptable trade-off between code complexity, computational load, and personal sanity.
-        //
-        // Besides, the error is less than 100m and only ever applies to non-reversible locations of which there are not many. Soooo ... 🤷‍♂️
-        for angle_deg in 0..18 {
-            let angle = (-(angle_deg as f64) * 10.0).to_radians();
-            let ray_origin = point_on_circle(angle);
-            let ray = angle - FRAC_PI_2;
-            let ray_typed = Angle::new::<radian>(ray);
-
-            let distance = binary_search(0.0, maximum_range, preferences.epsilon, |range| {
-                let ray_target = ray_origin + Point::new(range * ray.cos(), range * ray.sin());
-
-                let path_candidates = calculate_dubin_path_candidates(
-                    ray_target,
-                    origin,
-                    // TODO Make it an option to "invert" the start angle and thus get a "best case" range. Just out of curiosity on how much it changes the ranges :P
-                    ray_typed,
-                    origin_angle,
-                    radius,
-                );
+        // Height lost and distance flown on the shortest path from a point and heading (in radians) to the threshold
+        let shortest_path = |position: Point<f64>, heading: f64| {
+            calculate_dubin_path_candidates(
+                position,
+                origin,
+                Angle::new::<radian>(heading),
+                origin_angle,
+                radius,
+            )
+            .into_iter()
+            .map(|path| {
+                (
+                    aircraft
+                        .glide
+                        .height_loss_over_geometric_path(&path, preferences.bank),
+                    path.length().get::<meter>(),
+                )
+            })
+            .fold((f64::INFINITY, 0.0), |shortest, path| {
+                if path.0 < shortest.0 {
+                    path
+                } else {
+                    shortest
+                }
+            })
+        };
+        let height_loss = |position: Point<f64>, heading: f64| shortest_path(position, heading).0;
+
+        // Worst (or best) possible initial heading in terms of height loss. The best heading points straight along
+        // a tangent onto an approach circle, so checking the critical headings of the Dubins paths suffices. Most worst
+        // headings lie at them as well, the evenly spaced samples catch those in between before refining the result.
+        let extreme_heading = |position: Point<f64>, worst: bool| {
+            let exceeds = |loss: f64, other: f64| if worst { loss > other } else { loss < other };
+            let step = 2.0 * PI / HEADING_SAMPLES as f64;
+            let samples = if worst { HEADING_SAMPLES } else { 0 };
+            let candidates = critical_start_angles(position, origin, origin_angle, radius)
+                .into_iter()
+                .map(|angle| angle.get::<radian>())
+                .chain((0..samples).map(|i| i as f64 * step));
+
+            let (mut heading, mut loss) = (0.0, f64::NAN);
+            for candidate in candidates {
+                let candidate_loss = height_loss(position, candidate);
+
+                if loss.is_nan() || exceeds(candidate_loss, loss) {
+                    heading = candidate;
+                    loss = candidate_loss;
+                }
 
-                let minimum_height_loss = path_candidates
-                    .into_iter()
-                    .map(|path| {
-                        aircraft
-                            .glide
-                            .height_loss_over_geometric_path(&path, preferences.bank)
-                    })
-                    .min_by(|x, y| x.partial_cmp(y).unwrap());
+                if worst && loss > limit {
+                    return heading;
+                }
+            }
 
-                minimum_height_loss.map(|h| h <= altitude).unwrap_or(true)
-            });
+            if !worst {
+                return heading;
+            }
 
-            points[angle_deg] = ray_origin + Point::new(distance * ray.cos(), distance * ray.sin());
-        }
+            let mut step = step;
+            for _ in 0..HEADING_REFINEMENTS {
+                step /= 2.0;
+
+                for candidate in [heading - step, heading + step] {
+                    let candidate_loss = height_loss(position, candidate);
+
+                    if exceeds(candidate_loss, loss) {
+                        heading = candidate;
+                        loss = candidate_loss;
+                    }
+                }
+            }
+
+            heading
+        };
+
+        let heading = match initial_heading {
+            InitialHeading::Worst => extreme_heading(position, true),
+            InitialHeading::Best => extreme_heading(position, false),
+            InitialHeading::Fixed(heading) => heading,
+        };
+
+        shortest_path(position, heading)
+    }
+
+    /// Runways of a location, cleared of hazards, which do not classify as unsafe in terms of landing headroom.
+    /// Falls back to the longest runway if none of them are usable, which is absent for areas without a landable line.
+    fn usable_runways(
+        &self,
+        preferences: &Preferences,
+        location: &Location,
+        aircraft: &Aircraft,
+    ) -> Vec<Runway> {
+        let cleared_runways = location.cleared_runways(aircraft);
+        let longest_runway = cleared_runways
+            .iter()
+            .max_by(|a, b| a.length().partial_cmp(&b.length()).unwrap())
+            .copied()
+            .or_else(|| location.primary_runway());
+
+        let runways = cleared_runways
+            .into_iter()
+            .filter(|runway| {
+                location.runway_landing_headroom(runway, aircraft)
+                    >= preferences.unsafe_landing_headroom
+            })
+            .collect::<Vec<_>>();
 
-        AircraftRangeProfile(points)
+        if runways.is_empty() {
+            longest_runway.into_iter().collect()
+        } else {
+            runways
+        }
     }
 
+    /// Range profile of a single landing direction. Points alongside and beyond the threshold may also be
+    /// served by touchdown points further into the runway. The union of the profiles at every inset is bounded
+    /// by the profile at the threshold on the approach side and the one shifted by the full inset on the other.
+    /// While gliding towards the threshold the aircraft drifts with the wind, so each point is moved upwind
+    /// by the distance the air mass travels in the time it takes to fly there.
     fn location_range_profile(
         &self,
         location: &Location,
+        runway: &Runway,
         aircraft: &Aircraft,
         aircraft_range_profile: &AircraftRangeProfile,
+        wind: Point<f64>,
     ) -> LocationRangeProfile {
-        let inset = location.inset(aircraft);
-        let length = location.length();
+        let inset = location.runway_inset(runway, aircraft).max(0.0);
+        let speed = aircraft.glide.speed().get::<meter_per_second>();
 
-        let mut points = [Point::new(0.0, 0.0); 18];
+        // The landing direction points along the positive y-axis of the profile
+        let wind_angle =
+            (wind.x().atan2(wind.y()).to_degrees() - runway.bearing() + 90.0).to_radians();
+        let wind = Point::new(wind_angle.cos(), wind_angle.sin())
+            * wind.euclidean_distance(&Point::new(0.0, 0.0));
 
-        // Step 1: Copy over the first 90º of points
-        for i in 0..9 {
-            points[i] = aircraft_range_profile.0[i];
-        }
+        let points = aircraft_range_profile
+            .0
+            .iter()
+            .zip(aircraft_range_profile.1.iter())
+            .map(|(point, distance)| {
+                let point = if point.y() > 0.0 {
+                    Point::new(point.x(), point.y() + inset)
+                } else {
+                    *point
+                };
 
-        // Step 2.1: If the location is NOT reversible, copy over and inset the second 90º of points
-        if !location.reversible {
-            for i in 0..9 {
-                let mut point = aircraft_range_profile.0[17 - i].clone();
-                point.0.y += inset;
-                points[17 - i] = point;
-            }
-        }
-        // Step 2.2: If the location IS reversible, flip, copy, and offset the first 90º of points
-        else {
-            for i in 0..9 {
-                let mut point = aircraft_range_profile.0[i].clone();
-                point.0.y *= -1.0;
-                point.0.y += length;
-                points[17 - i] = point;
-            }
-        }
+                point - wind * (distance / speed)
+            })
+            .collect();
 
         LocationRangeProfile(points)
     }
 
+    /// Area from which the location can be reached in one of the landing directions, the profile is calculated
+    /// for each direction if none is shared
     fn location_range_polygon(
         &self,
+        preferences: &Preferences,
         location: &Location,
+        directions: &[Runway],
         aircraft: &Aircraft,
-        aircraft_range_profile: &AircraftRangeProfile,
-    ) -> Polygon<f64> {
-        // Step 0: Generate a range profile
-        let profile = self.location_range_profile(location, aircraft, aircraft_range_profile);
+        altitude: f64,
+        shared_range_profile: Option<&AircraftRangeProfile>,
+        wind: Point<f64>,
+    ) -> MultiPolygon<f64> {
+        let mut polygons = directions
+            .iter()
+            .map(|direction| match shared_range_profile {
+                Some(profile) => {
+                    self.runway_range_polygon(location, direction, aircraft, profile, wind)
+                }
+                None => {
+                    let profile = self.runway_heading_range_profile(
+                        preferences,
+                        aircraft,
+                        altitude,
+                        direction,
+                    );
+                    self.runway_range_polygon(location, direction, aircraft, &profile, wind)
+                }
+            });
 
-        // Step 1: Mirror the profile along the Y-axis to cover the full 360º
-        let mut mirrored_profile = LocationRangeProfile([Point::new(0.0, 0.0); 18]);
+        let first = MultiPolygon(polygons.next().into_iter().collect());
+        polygons.fold(first, |union, polygon| union.union(&polygon))
+    }
 
-        for i in 0..18 {
-            mirrored_profile.0[i] = profile.0[17 - i];
-            mirrored_profile.0[i].0.x *= -1.0;
-        }
+    /// Range polygon of a single landing direction
+    fn runway_range_polygon(
+        &self,
+        location: &Location,
+        runway: &Runway,
+        aircraft: &Aircraft,
+        aircraft_range_profile: &AircraftRangeProfile,
+        wind: Point<f64>,
+    ) -> Polygon<f64> {
+        // Step 1: Generate a range profile
+        let profile =
+            self.location_range_profile(location, runway, aircraft, aircraft_range_profile, wind);
 
-        // Step 2: Create the points for a LineString from the two sides,
-        //         rotate it to match the locations heading,
+        // Step 2: Rotate the profile to match the runways heading
         //         and convert from relative geometrics points to absolute geographic coordinates.
         let origin = Point::new(0.0, 0.0);
         let points = profile
             .0
             .iter()
-            .chain(mirrored_profile.0.iter())
             .map(|p| {
-                let rotated_point = p.rotate_around_point(location.bearing(), origin);
+                let rotated_point = p.rotate_around_point(runway.bearing(), origin);
                 let bearing = rotated_point.y().atan2(rotated_point.x()).to_degrees() - 90.0;
                 let distance = rotated_point.euclidean_distance(&origin);
 
-                location.start().haversine_destination(bearing, distance).0
+                runway.start.haversine_destination(bearing, distance)
             })
             .collect::<Vec<_>>();
 
-        // Step 3: Convert it into a polygon and profit!
-        Polygon::new(LineString(points), vec![])
-    }
-}
+        // Step 3: Cut off the range where the terrain rises above the glide towards the threshold
+        let points = match &self.terrain {
+            Some(terrain) => {
+                let threshold =
+                    Length::new::<foot>(location.elevation as f64 + SCREEN_HEIGHT).get::<meter>();
+                let speed = aircraft.glide.speed().get::<meter_per_second>();
 
-#[wasm_bindgen]
-impl Calculator {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
-        Self {}
-    }
+                points
+                    .into_iter()
+                    .map(|point| {
+                        // A headwind steepens the descent over the ground while a tailwind flattens it
+                        let course = (runway.start.bearing(point) + 180.0).to_radians();
+                        let ground_speed =
+                            speed + wind.x() * course.sin() + wind.y() * course.cos();
+                        let steepening = speed / ground_speed.max(f64::EPSILON);
+
+                        let obstruction = terrain.obstruction(
+                            &[runway.start, point],
+                            TERRAIN_RESOLUTION,
+                            |distance| {
+                                threshold
+                                    + aircraft.glide.height_lost_for_ground_track(distance)
+                                        * steepening
+                            },
+                        );
+
+                        match obstruction {
+                            Some(distance) => runway
+                                .start
+                                .haversine_destination(runway.start.bearing(point), distance),
+                            None => point,
+                        }
+                    })
+                    .collect()
+            }
+            None => points,
+        };
 
-    #[wasm_bindgen(js_name = "locationHitboxes")]
-    pub fn location_hitboxes(
-        &self,
-        location_map: &LocationMap,
-        distance: f64,
-    ) -> Result<String, JsValue> {
-        let features = location_map
-            .locations()
-            .map(|location| {
-                let centroid = location.centroid();
-                let mut properties = Map::new();
-                properties.insert("id".into(), location.id().into());
-                properties.insert("lat".into(), centroid.lat().into());
-                properties.insert("lng".into(), centroid.lng().into());
+        // Step 4: Convert it into a polygon and profit!
+        Polygon::new(
+            LineString(points.into_iter().map(|p| p.0).collect()),
+            vec![],
+        )
+    }
 
-                Feature {
-                    bbox: None,
-                    geometry: Some((&location.spaced_polygon(distance)).into()),
-                    id: Some(Id::String(location.id())),
-                    properties: Some(properties),
-                    foreign_members: None,
-                }
-            })
-            .collect();
+    /// Whether the terrain stays below an aircraft which glides along the path from the given altitude (in meters above MSL)
+    fn clears_terrain(&self, path: &GeographicDubinPath, altitude: f64, height_loss: f64) -> bool {
+        let terrain = match &self.terrain {
+            Some(terrain) => terrain,
+            None => return true,
+        };
 
-        let geojson = GeoJson::FeatureCollection(FeatureCollection {
-            bbox: None,
-            features,
-            foreign_members: None,
-        });
+        let points = path.points().collect::<Vec<_>>();
+        let length = points
+            .windows(2)
+            .map(|segment| segment[0].haversine_distance(&segment[1]))
+            .sum::<f64>();
 
-        Ok(serde_json::to_string(&geojson).map_err(|e| e.to_string())?)
+        terrain
+            .obstruction(&points, TERRAIN_RESOLUTION, |distance| {
+                altitude - height_loss * distance / length.max(1.0)
+            })
+            .is_none()
     }
 
-    #[wasm_bindgen(js_name = assessRisk)]
-    pub fn assess_risk(
+    /// Areas from which the locations can be reached at a single altitude, both by risk and by location.
+    /// Risk assessments, landing directions and range profiles do not depend on the altitude and are cached across calls.
+    fn reachability_at(
         &self,
         preferences: &Preferences,
-        location: &Location,
+        location_map: &LocationMap,
         aircraft: &Aircraft,
-    ) -> RiskAssessment {
-        use RiskClassification::*;
+        altitude: f64,
+        time: &DateTime<FixedOffset>,
+        bounds: Option<&[f64]>,
+        envelopes: bool,
+        altitude_reference: AltitudeReference,
+        wind: Option<&WindConditions>,
+        cache: &mut ReachabilityCache,
+    ) -> Result<serde_json::Value, String> {
+        // Step 1: Calculate and cache the aircraft range profiles, which only depend on the height above a location.
+        //         Like the landing paths, they end when crossing the threshold at screen height.
+        let screen_height = Length::new::<foot>(SCREEN_HEIGHT).get::<meter>();
+        let height_above = |location: &Location| {
+            let elevation = match altitude_reference {
+                AltitudeReference::MSL => {
+                    Length::new::<foot>(location.elevation as f64).get::<meter>()
+                }
+                _ => 0.0,
+            };
 
-        // Step 1: Check surface type
-        let surface = match location.surface {
-            SurfaceType::Water => Unsafe,
-            _ => Safe,
+            altitude - elevation - screen_height
         };
-
-        // Step 2: Verify landing headroom
-        let headroom = match location.landing_headroom(aircraft) {
-            headroom if headroom < preferences.unsafe_landing_headroom => Unsafe,
-            headroom if headroom < preferences.risky_landing_headroom => Risky,
-            _ => Safe,
+        let key = |height: f64| (height / HEIGHT_RESOLUTION).round() as i64;
+        let range_profiles = |height: f64| RangeProfiles {
+            shared: self.shared_range_profile(
+                preferences,
+                aircraft,
+                height,
+                preferences.profile_mode,
+            ),
+            envelopes: envelopes.then(|| {
+                [InitialHeading::Worst, InitialHeading::Best].map(|initial_heading| {
+                    self.aircraft_range_profile(preferences, aircraft, height, initial_heading)
+                })
+            }),
+        };
+        // Wind altitudes use the same reference as the aircraft altitude
+        let mean_wind = |height: f64| {
+            wind.map(|wind| wind.mean_velocity(altitude - height, altitude))
+                .unwrap_or_else(|| Point::new(0.0, 0.0))
         };
+        let mut winds = HashMap::new();
+
+        // No heading reaches further than the best one, which in turn is bounded by the straight line.
+        // The elevation of locations is not known up front, so with MSL altitudes the straight line has to do.
+        // No path through the air is longer than the straight glide, which bounds how far the wind may carry it.
+        let drift =
+            wind.map_or(0.0, |wind| wind.maximum_speed()) * aircraft.glide.ratio() * altitude
+                / aircraft.glide.speed().get::<meter_per_second>();
+        let reach = drift
+            + match altitude_reference {
+                AltitudeReference::MSL => aircraft.glide.ratio() * altitude,
+                _ => {
+                    let height = altitude - screen_height;
+                    let profiles = cache
+                        .profiles
+                        .entry(key(height))
+                        .or_insert_with(|| range_profiles(height));
+
+                    match (&profiles.envelopes, &profiles.shared) {
+                        (Some([_, best_case]), _) => best_case.reach(),
+                        (None, Some(profile)) => profile.reach(),
+                        (None, None) => aircraft.glide.ratio() * height,
+                    }
+                }
+            };
+
+        // Step 2: Skip locations whose range can not reach into the visible bounds [west, south, east, north]
+        let locations = match bounds {
+            Some(&[west, south, east, north]) => {
+                let bounds = Rect::new(
+                    Coordinate { x: west, y: south },
+                    Coordinate { x: east, y: north },
+                );
 
-        // Step 3: Check for human presence
-        let event_risky = preferences.event_location_classification == RiskClassification::Risky;
-        let event_unsafe = preferences.event_location_classification == RiskClassification::Unsafe;
-        let dense_risky = preferences.densely_crowded_classification == RiskClassification::Risky;
-        let dense_unsafe = preferences.densely_crowded_classification == RiskClassification::Unsafe;
-
-        let humans = match location.human_presence {
-            HumanPresenceCategory::EventOnly if event_risky => Risky,
-            HumanPresenceCategory::EventOnly if event_unsafe => Unsafe,
-            HumanPresenceCategory::Dense if dense_risky => Risky,
-            HumanPresenceCategory::Dense if dense_unsafe => Unsafe,
-            _ => Safe,
+                location_map.within_bounds(&expand_bounds(&bounds, reach))
+            }
+            Some(_) => return Err("Bounds must consist of [west, south, east, north]".into()),
+            None => location_map.locations().collect(),
         };
 
-        // Step 4: Profit!
-        RiskAssessment {
-            overall: surface + headroom + humans,
-            surface,
-            headroom,
-            humans,
+        // Fields with standing crops can not be landed on and locations above the aircraft can not be reached
+        let locations = locations
+            .into_iter()
+            .filter(|location| location.standing_crop_at(time).is_none())
+            .filter(|location| height_above(location) > 0.0)
+            .collect::<Vec<_>>();
+
+        for location in locations.iter() {
+            let height = height_above(location);
+            cache
+                .profiles
+                .entry(key(height))
+                .or_insert_with(|| range_profiles(height));
+            winds
+                .entry(key(height))
+                .or_insert_with(|| mean_wind(height));
+
+            cache.locations.entry(location.id()).or_insert_with(|| {
+                let directions = self
+                    .usable_runways(preferences, location, aircraft)
+                    .into_iter()
+                    .flat_map(|runway| runway.directions())
+                    .collect();
+
+                (
+                    self.assess_risk_at(preferences, location, aircraft, time),
+                    directions,
+                )
+            });
         }
-    }
 
-    #[wasm_bindgen(js_name = reachabilityGeoJSON)]
-    pub fn reachability_geojson(
-        &self,
-        preferences: &Preferences,
-        location_map: &LocationMap,
-        aircraft: &Aircraft,
-        altitude: f64,
-    ) -> Result<String, JsValue> {
-        // Step 1: Calculate and cache the aircraft range profile
-        let aircraft_range_profile = self.aircraft_range_profile(preferences, aircraft, altitude);
+        let profiles_of = |location: &Location| &cache.profiles[&key(height_above(location))];
+        let wind_of = |location: &Location| winds[&key(height_above(location))];
+        let cached = |location: &Location| &cache.locations[&location.id()];
+
+        // Step 2.5: Union the areas from which any location is reachable with the worst and best heading
+        let envelopes = envelopes.then(|| {
+            let modes = [ProfileMode::WorstCase, ProfileMode::BestCase];
+            let features = modes
+                .iter()
+                .enumerate()
+                .map(|(index, mode)| {
+                    let mut polygons = locations.iter().map(|location| {
+                        let envelopes = profiles_of(location).envelopes.as_ref();
+
+                        self.location_range_polygon(
+                            preferences,
+                            location,
+                            &cached(location).1,
+                            aircraft,
+                            height_above(location),
+                            envelopes.map(|envelopes| &envelopes[index]),
+                            wind_of(location),
+                        )
+                    });
+
+                    let first = polygons.next().unwrap_or_else(|| MultiPolygon(vec![]));
+                    let polygon = polygons.fold(first, |union, polygon| union.union(&polygon));
+
+                    let mut properties = Map::new();
+                    properties.insert("mode".into(), to_value(mode).unwrap());
+
+                    Feature {
+                        bbox: None,
+                        geometry: Some((&polygon).into()),
+                        id: None,
+                        properties: Some(properties),
+                        foreign_members: None,
+                    }
+                })
+                .collect();
+
+            GeoJson::FeatureCollection(FeatureCollection {
+                bbox: None,
+                features,
+                foreign_members: None,
+            })
+        });
 
-        // Step 2: Create polygons and assess risk for each location
-        let polygons = location_map.locations().map(|location| {
+        // Step 3: Create polygons for each location, whose risk has been assessed along with its landing directions
+        let polygons = locations.into_iter().map(|location| {
             (
-                self.assess_risk(preferences, location, aircraft).overall,
-                self.location_range_polygon(location, aircraft, &aircraft_range_profile),
+                &cached(location).0,
+                self.location_range_polygon(
+                    preferences,
+                    location,
+                    &cached(location).1,
+                    aircraft,
+                    height_above(location),
+                    profiles_of(location).shared.as_ref(),
+                    wind_of(location),
+                ),
                 location.id(),
             )
         });
 
-        // Step 3: Group and union the polygons by risk and create individual features
+        // Step 4: Group and union the polygons by risk and create individual features
         let (mut risk_map, feature_map): (
             HashMap<RiskClassification, MultiPolygon<f64>>,
             HashMap<String, Feature>,
         ) = polygons.fold(
             (HashMap::new(), HashMap::new()),
-            |(mut risk_map, mut feature_map), (risk, polygon, id)| {
+            |(mut risk_map, mut feature_map), (assessment, polygon, id)| {
+                let risk = assessment.overall;
+
                 // Create an individual geojson feature
                 let mut properties = Map::new();
                 properties.insert("id".into(), id.clone().into());
                 properties.insert("risk".into(), to_value(risk).unwrap());
+                properties.insert("score".into(), to_value(assessment.score).unwrap());
+                properties.insert("reasons".into(), to_value(&assessment.reasons).unwrap());
                 let feature = Feature {
                     bbox: None,
                     geometry: Some((&polygon).into()),
@@ -343,7 +846,7 @@ impl Calculator {
                 // Union the polygon with the corresponding risk category
                 let polygon = match risk_map.remove(&risk) {
                     Some(existing_polygon) => existing_polygon.union(&polygon),
-                    None => MultiPolygon(vec![polygon]),
+                    None => polygon,
                 };
 
                 risk_map.insert(risk, polygon);
@@ -351,7 +854,7 @@ impl Calculator {
             },
         );
 
-        // Step 4: Subtract lower risk polygons from higher risk ones so they do not overlap on the map
+        // Step 5: Subtract lower risk polygons from higher risk ones so they do not overlap on the map
         RiskClassification::iter().fold(vec![], |mut less_risky_polygons, risk| {
             match risk_map.get_mut(&risk) {
                 Some(polygon) => {
@@ -366,7 +869,7 @@ impl Calculator {
             }
         });
 
-        // Step 5: Convert into a vector of features with an associated risk property
+        // Step 6: Convert into a vector of features with an associated risk property
         let features = risk_map
             .into_iter()
             .map(|(risk, polygon)| {
@@ -389,146 +892,1185 @@ impl Calculator {
             foreign_members: None,
         });
 
-        let combined = serde_json::json!({
+        Ok(serde_json::json!({
             "byRisk": by_risk_geojson,
-            "byID": feature_map
-        });
-
-        Ok(serde_json::to_string(&combined).map_err(|e| e.to_string())?)
+            "byID": feature_map,
+            "envelopes": envelopes
+        }))
     }
 
-    #[wasm_bindgen(js_name = locationGeoJSON)]
-    pub fn location_geojson(
+    /// Shortest glide path from a point and heading (in degrees) to every reachable location together with its
+    /// height loss, the height to spare and the inset of the touchdown point. The altitude (in meters) is
+    /// interpreted relative to the given reference.
+    fn landing_paths<'a>(
         &self,
         preferences: &Preferences,
-        location_map: &LocationMap,
+        start: Point<f64>,
+        heading: f64,
+        altitude: f64,
+        aircraft: &Aircraft,
+        locations: &'a LocationMap,
+        altitude_reference: AltitudeReference,
+    ) -> Vec<(GeographicDubinPath, f64, f64, f64, &'a Location)> {
+        let radius = Length::new::<meter>(aircraft.glide.turn_radius(preferences.bank));
+        let screen_height = Length::new::<foot>(SCREEN_HEIGHT).get::<meter>();
+
+        // No path to a location can be shorter than the straight line, thus anything beyond gliding distance is out of reach
+        let maximum_range = aircraft.glide.ratio() * altitude;
+
+        locations
+            .within_radius(start, maximum_range)
+            .into_iter()
+            .filter_map(|location| {
+                // Paths end when crossing the threshold at screen height, where the landing distance begins
+                let elevation = match altitude_reference {
+                    AltitudeReference::MSL => {
+                        Length::new::<foot>(location.elevation as f64).get::<meter>()
+                    }
+                    _ => 0.0,
+                };
+                let available_height = altitude - elevation - screen_height;
+                let altitude_msl = match altitude_reference {
+                    AltitudeReference::AGL => {
+                        altitude + Length::new::<foot>(location.elevation as f64).get::<meter>()
+                    }
+                    _ => altitude,
+                };
+
+                // Touching down further into long runways may shorten the path considerably
+                self.usable_runways(preferences, location, aircraft)
+                    .iter()
+                    .flat_map(|runway| {
+                        location.runway_landable_points(runway, aircraft, TOUCHDOWN_RESOLUTION)
+                    })
+                    .flat_map(|(end, target_heading, inset)| {
+                        let start_bearing = Angle::new::<degree>(heading);
+                        let end_bearing = Angle::new::<degree>(target_heading);
+
+                        calculate_georeferenced_dubin_path_candidates(
+                            start,
+                            end,
+                            start_bearing,
+                            end_bearing,
+                            radius,
+                        )
+                        .into_iter()
+                        .map(move |path| (path, inset))
+                    })
+                    .map(|(path, inset)| {
+                        let height_loss = aircraft
+                            .glide
+                            .height_loss_over_geographic_path(&path, preferences.bank);
+
+                        (path, height_loss, inset)
+                    })
+                    .filter(|(path, height_loss, _)| {
+                        *height_loss < available_height
+                            && self.clears_terrain(path, altitude_msl, *height_loss)
+                    })
+                    .min_by(|(_, height_loss_a, _), (_, height_loss_b, _)| {
+                        height_loss_a
+                            .partial_cmp(&height_loss_b)
+                            .unwrap_or(Ordering::Equal)
+                    })
+                    .map(|(path, height_loss, inset)| {
+                        let margin = available_height - height_loss;
+
+                        (path, height_loss, margin, inset, location)
+                    })
+            })
+            .collect()
+    }
+
+    /// Lowest altitude from which a location of the given risk or less can be reached from a point, together with
+    /// that location. Without a track (in degrees) the worst initial heading is assumed. Landing directions are
+    /// visited by the altitude a straight glide would require, so the search ends once none of them can do better.
+    /// Risks and landing directions of the locations are cached across calls.
+    fn minimum_altitude<'a>(
+        &self,
+        preferences: &Preferences,
+        locations: &'a LocationMap,
+        aircraft: &Aircraft,
+        position: Point<f64>,
+        track: Option<f64>,
+        risk: RiskClassification,
+        ceiling: f64,
+        altitude_reference: AltitudeReference,
+        time: &DateTime<FixedOffset>,
+        cache: &mut HashMap<String, (RiskClassification, Vec<Runway>)>,
+    ) -> Option<(f64, &'a Location)> {
+        let screen_height = Length::new::<foot>(SCREEN_HEIGHT).get::<meter>();
+
+        let mut candidates = Vec::new();
+        for location in locations.within_radius(position, aircraft.glide.ratio() * ceiling) {
+            let (overall, directions) = cache.entry(location.id()).or_insert_with(|| {
+                let directions = self
+                    .usable_runways(preferences, location, aircraft)
+                    .into_iter()
+                    .flat_map(|runway| runway.directions())
+                    .collect();
+
+                (
+                    self.assess_risk_at(preferences, location, aircraft, time)
+                        .overall,
+                    directions,
+                )
+            });
+
+            if Into::<u8>::into(*overall) > Into::<u8>::into(risk) {
+                continue;
+            }
+
+            let elevation = match altitude_reference {
+                AltitudeReference::MSL => {
+                    Length::new::<foot>(location.elevation as f64).get::<meter>()
+                }
+                _ => 0.0,
+            };
+
+            // Touchdown points lie within the inset beyond the threshold and no path is shorter than the straight line
+            for direction in directions.iter() {
+                let inset = location.runway_inset(direction, aircraft).max(0.0);
+                let distance = direction.start.haversine_distance(&position);
+                let bound = elevation
+                    + screen_height
+                    + aircraft
+                        .glide
+                        .height_lost_for_ground_track((distance - inset).max(0.0));
+
+                candidates.push((bound, elevation, inset, distance, *direction, location));
+            }
+        }
+
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let mut minimum: Option<(f64, &Location)> = None;
+        for (bound, elevation, inset, distance, direction, location) in candidates {
+            if bound > ceiling || minimum.map_or(false, |(minimum, _)| bound >= minimum) {
+                break;
+            }
+
+            // Locate the point within the range profile of the landing direction
+            let bearing = direction.start.bearing(position);
+            let angle = (bearing - direction.bearing() + 90.0).to_radians();
+            let local = Point::new(angle.cos(), angle.sin()) * distance;
+
+            let initial_heading = match track {
+                Some(track) => {
+                    InitialHeading::Fixed((track - direction.bearing() + 90.0).to_radians())
+                }
+                None => InitialHeading::Worst,
+            };
+
+            // Alongside and beyond the threshold, touching down further into the runway may shorten the path.
+            // Touchdown points are visited from the nearest one on, as for the landing directions.
+            let touchdowns = if local.y() > 0.0 {
+                (inset / TOUCHDOWN_RESOLUTION).floor() as usize
+            } else {
+                0
+            };
+
+            let mut touchdowns = (0..=touchdowns)
+                .map(|step| local - Point::new(0.0, step as f64 * TOUCHDOWN_RESOLUTION))
+                .map(|local| (local.euclidean_distance(&Point::new(0.0, 0.0)), local))
+                .collect::<Vec<_>>();
+            touchdowns.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+            for (distance, local) in touchdowns {
+                let bound = elevation
+                    + screen_height
+                    + aircraft.glide.height_lost_for_ground_track(distance);
+
+                if bound > ceiling || minimum.map_or(false, |(minimum, _)| bound >= minimum) {
+                    break;
+                }
+
+                let limit = minimum.map_or(ceiling, |(minimum, _)| minimum.min(ceiling))
+                    - elevation
+                    - screen_height;
+                let (height_loss, _) =
+                    self.profile_path(preferences, aircraft, local, initial_heading, limit);
+                let altitude = elevation + screen_height + height_loss;
+                let lower = minimum.map_or(true, |(minimum, _)| altitude < minimum);
+
+                if altitude <= ceiling && lower {
+                    minimum = Some((altitude, location));
+                }
+            }
+        }
+
+        minimum
+    }
+
+    /// Parses a route passed in from JS as a GeoJSON LineString or a feature thereof
+    fn parse_route(route: &str) -> Result<Vec<Point<f64>>, JsValue> {
+        let geometry = match route.parse::<GeoJson>().map_err(|e| e.to_string())? {
+            GeoJson::Geometry(geometry) => Some(geometry),
+            GeoJson::Feature(feature) => feature.geometry,
+            GeoJson::FeatureCollection(_) => None,
+        };
+
+        match geometry.map(|geometry| geometry.value) {
+            Some(geojson::Value::LineString(coordinates)) if coordinates.len() > 1 => {
+                Ok(coordinates
+                    .into_iter()
+                    .map(|coordinate| Point::new(coordinate[0], coordinate[1]))
+                    .collect())
+            }
+            _ => Err("Route must be a LineString of at least two points".into()),
+        }
+    }
+
+    /// Points spaced evenly along each leg of a route, including its end, as the distance travelled (in meters),
+    /// position and track (in degrees)
+    fn route_samples(route: &[Point<f64>]) -> Vec<(f64, Point<f64>, f64)> {
+        let mut samples = Vec::new();
+        let mut travelled = 0.0;
+
+        for (index, segment) in route.windows(2).enumerate() {
+            let (start, end) = (segment[0], segment[1]);
+            let length = start.haversine_distance(&end);
+            let heading = start.bearing(end);
+            let last = index == route.len() - 2;
+            let count = (length / ROUTE_RESOLUTION).ceil().max(1.0) as usize;
+
+            for i in 0..(count + last as usize) {
+                let fraction = i as f64 / count as f64;
+                samples.push((
+                    travelled + length * fraction,
+                    start + (end - start) * fraction,
+                    heading,
+                ));
+            }
+
+            travelled += length;
+        }
+
+        samples
+    }
+
+    /// Parses a point in time passed in from JS (RFC 3339), defaulting to the current local time
+    pub(crate) fn parse_time(time: Option<String>) -> Result<DateTime<FixedOffset>, JsValue> {
+        match time {
+            Some(time) => Ok(DateTime::parse_from_rfc3339(&time).map_err(|e| e.to_string())?),
+            None => Ok(Local::now().into()),
+        }
+    }
+
+    /// Parses wind conditions passed in from JS as JSON, either a single wind or a list of layers
+    fn parse_wind(wind: Option<String>) -> Result<Option<WindConditions>, JsValue> {
+        match wind {
+            Some(wind) => Ok(Some(
+                serde_json::from_str(&wind).map_err(|e| e.to_string())?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Days passed between the survey of a location and the given point in time, `None` if the survey date is unknown
+    fn survey_age(location: &Location, time: &DateTime<FixedOffset>) -> Option<i64> {
+        location
+            .surveyed_at()
+            .map(|surveyed_at| time.signed_duration_since(surveyed_at).num_days())
+    }
+
+    /// Facts about the location at the given point in time which do not depend on an aircraft.
+    /// Rules conditioned on an aircraft never apply to them, and the location is considered unobstructed with ample headroom.
+    fn location_facts(
+        &self,
+        location: &Location,
+        time: &DateTime<FixedOffset>,
+    ) -> RiskFacts<'static> {
+        let water = location.surface == SurfaceType::Water;
+
+        RiskFacts {
+            surface: location.surface,
+            usage: location.usage,
+            presence: location.human_presence_at(time),
+            obstruction: Obstruction::None,
+            ditching: water.then(|| {
+                self.assess_ditching(location)
+                    .map(|d| d.overall)
+                    .unwrap_or(RiskClassification::Unsafe)
+            }),
+            standing_crop: location.standing_crop_at(time).is_some(),
+            headroom: f64::INFINITY,
+            survey_age: Self::survey_age(location, time),
+            aircraft: "",
+            aircraft_category: None,
+        }
+    }
+
+    pub fn assess_risk_at(
+        &self,
+        preferences: &Preferences,
+        location: &Location,
+        aircraft: &Aircraft,
+        time: &DateTime<FixedOffset>,
+    ) -> RiskAssessment {
+        // Step 1: Gather the facts the rules may refer to
+        let ditching = self.assess_ditching(location);
+
+        let longest = |runways: Vec<Runway>| runways.iter().map(Runway::length).fold(0.0, f64::max);
+        let obstruction = match longest(location.cleared_runways(aircraft)) {
+            length if length <= 0.0 => Obstruction::Complete,
+            length if length < longest(location.runways()) => Obstruction::Partial,
+            _ => Obstruction::None,
+        };
+
+        let facts = RiskFacts {
+            obstruction,
+            headroom: location.landing_headroom(aircraft),
+            aircraft: &aircraft.id,
+            aircraft_category: aircraft.category.as_deref(),
+            ..self.location_facts(location, time)
+        };
+
+        // Step 2: Classify every factor as the most severe of the reasons the rules give
+        let mut reasons = self.rules.reasons(preferences, &facts);
+        let classify = |factor| {
+            reasons
+                .iter()
+                .filter(|reason| reason.factor == factor)
+                .fold(RiskClassification::Safe, |risk, reason| {
+                    risk + reason.classification
+                })
+        };
+
+        let surface = classify(RiskFactor::Surface);
+        let headroom = classify(RiskFactor::Headroom);
+        let humans = classify(RiskFactor::Humans);
+        let obstacles = classify(RiskFactor::Obstacles);
+        let crops = classify(RiskFactor::Crops);
+        let staleness = classify(RiskFactor::Staleness);
+
+        let factors = [
+            (RiskFactor::Surface, surface),
+            (RiskFactor::Headroom, headroom),
+            (RiskFactor::Humans, humans),
+            (RiskFactor::Obstacles, obstacles),
+            (RiskFactor::Crops, crops),
+            (RiskFactor::Staleness, staleness),
+        ];
+
+        // Less severe rules for the same factor do not contribute to the outcome
+        reasons.retain(|reason| factors.contains(&(reason.factor, reason.classification)));
+
+        // Step 3: Profit!
+        RiskAssessment {
+            overall: surface + headroom + humans + obstacles + crops + staleness,
+            score: risk_score(&factors, &reasons),
+            surface,
+            headroom,
+            humans,
+            obstacles,
+            crops,
+            staleness,
+            ditching,
+            reasons,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Calculator {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            rules: RiskRules::default(),
+            terrain: None,
+        }
+    }
+
+    /// Replaces the elevation model used to check glide paths for terrain clearance, removes it if none is given
+    #[wasm_bindgen(js_name = setTerrain)]
+    pub fn set_terrain(&mut self, terrain: Option<ElevationModel>) {
+        self.terrain = terrain;
+    }
+
+    /// Replaces the rules used to assess risks with the given YAML ruleset, restores the default ruleset if none is given
+    #[wasm_bindgen(js_name = setRules)]
+    pub fn set_rules(&mut self, yaml: Option<String>) -> Result<(), JsValue> {
+        self.rules = match yaml {
+            Some(yaml) => RiskRules::from_yaml(&yaml)?,
+            None => RiskRules::default(),
+        };
+
+        Ok(())
+    }
+
+    /// Ruleset currently used to assess risks as YAML
+    pub fn rules(&self) -> Result<String, JsValue> {
+        Ok(self.rules.to_yaml()?)
+    }
+
+    #[wasm_bindgen(js_name = "locationHitboxes")]
+    pub fn location_hitboxes(
+        &self,
+        location_map: &LocationMap,
+        distance: f64,
+    ) -> Result<String, JsValue> {
+        let features = location_map
+            .locations()
+            .filter_map(|location| {
+                let polygon = location.spaced_polygon(distance)?;
+                let centroid = location.centroid();
+                let mut properties = Map::new();
+                properties.insert("id".into(), location.id().into());
+                properties.insert("lat".into(), centroid.lat().into());
+                properties.insert("lng".into(), centroid.lng().into());
+
+                Some(Feature {
+                    bbox: None,
+                    geometry: Some((&polygon).into()),
+                    id: Some(Id::String(location.id())),
+                    properties: Some(properties),
+                    foreign_members: None,
+                })
+            })
+            .collect();
+
+        let geojson = GeoJson::FeatureCollection(FeatureCollection {
+            bbox: None,
+            features,
+            foreign_members: None,
+        });
+
+        Ok(serde_json::to_string(&geojson).map_err(|e| e.to_string())?)
+    }
+
+    /// Assesses the risk of landing at a location for the given point in time (RFC 3339, defaults to now)
+    #[wasm_bindgen(js_name = assessRisk)]
+    pub fn assess_risk(
+        &self,
+        preferences: &Preferences,
+        location: &Location,
+        aircraft: &Aircraft,
+        time: Option<String>,
+    ) -> Result<RiskAssessment, JsValue> {
+        let time = Self::parse_time(time)?;
+        Ok(self.assess_risk_at(preferences, location, aircraft, &time))
+    }
+
+    /// Grades a water location for ditching a landplane.
+    /// Returns `None` if the location is not on water or no ditching characteristics have been recorded.
+    #[wasm_bindgen(js_name = assessDitching)]
+    pub fn assess_ditching(&self, location: &Location) -> Option<DitchingAssessment> {
+        use RiskClassification::*;
+
+        if location.surface != SurfaceType::Water {
+            return None;
+        }
+
+        let characteristics = location.ditching.as_ref()?;
+
+        let width = match characteristics.width {
+            width if width < DITCHING_UNSAFE_WIDTH => Unsafe,
+            width if width < DITCHING_RISKY_WIDTH => Risky,
+            _ => Safe,
+        };
+
+        let shore = match characteristics.shore_distance {
+            distance if distance > DITCHING_UNSAFE_SHORE_DISTANCE => Unsafe,
+            distance if distance > DITCHING_RISKY_SHORE_DISTANCE => Risky,
+            _ => Safe,
+        };
+
+        let sea_state = match characteristics.sea_state {
+            SeaState::Calm => Safe,
+            SeaState::Moderate => Risky,
+            SeaState::Rough => Unsafe,
+            // Values unknown to wasm-bindgen are treated like the worst conditions
+            _ => Unsafe,
+        };
+
+        let rescue = match characteristics.rescue {
+            RescueProximity::OnSite | RescueProximity::Nearby => Safe,
+            RescueProximity::Remote => Risky,
+            _ => Unsafe,
+        };
+
+        // Ditching always damages the aircraft, so it can never be better than risky
+        Some(DitchingAssessment {
+            overall: Risky + width + shore + sea_state + rescue,
+            width,
+            shore,
+            sea_state,
+            rescue,
+            heading: location.ditching_heading()?,
+        })
+    }
+
+    /// Lists all locations ordered by how urgently they need to be surveyed again, those with the oldest
+    /// (or an unknown) survey date first. Each entry carries the age in days and the classification
+    /// the staleness rules of the active ruleset give it.
+    #[wasm_bindgen(js_name = resurveyReport)]
+    pub fn resurvey_report(
+        &self,
+        preferences: &Preferences,
+        location_map: &LocationMap,
+        time: Option<String>,
+    ) -> Result<String, JsValue> {
+        let time = Self::parse_time(time)?;
+
+        let mut locations = location_map
+            .locations()
+            .map(|location| (location, Self::survey_age(location, &time)))
+            .collect::<Vec<_>>();
+
+        locations.sort_by_key(|(_, age)| std::cmp::Reverse(age.unwrap_or(i64::MAX)));
+
+        let report = locations
+            .into_iter()
+            .map(|(location, age)| {
+                let facts = self.location_facts(location, &time);
+                let staleness = self
+                    .rules
+                    .reasons(preferences, &facts)
+                    .iter()
+                    .filter(|reason| reason.factor == RiskFactor::Staleness)
+                    .fold(RiskClassification::Safe, |risk, reason| {
+                        risk + reason.classification
+                    });
+
+                serde_json::json!({
+                    "id": location.id(),
+                    "name": location.name,
+                    "surveyDate": location.survey_date,
+                    "age": age,
+                    "staleness": staleness,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::to_string(&report).map_err(|e| e.to_string())?)
+    }
+
+    #[wasm_bindgen(js_name = reachabilityGeoJSON)]
+    pub fn reachability_geojson(
+        &self,
+        preferences: &Preferences,
+        location_map: &LocationMap,
+        aircraft: &Aircraft,
+        altitude: f64,
+        time: Option<String>,
+        bounds: Option<Box<[f64]>>,
+        envelopes: Option<bool>,
+        altitude_reference: AltitudeReference,
+        wind: Option<String>,
+    ) -> Result<String, JsValue> {
+        let time = Self::parse_time(time)?;
+        let wind = Self::parse_wind(wind)?;
+
+        let reachability = self.reachability_at(
+            preferences,
+            location_map,
+            aircraft,
+            altitude,
+            &time,
+            bounds.as_deref(),
+            envelopes.unwrap_or(false),
+            altitude_reference,
+            wind.as_ref(),
+            &mut ReachabilityCache::default(),
+        )?;
+
+        Ok(serde_json::to_string(&reachability).map_err(|e| e.to_string())?)
+    }
+
+    /// Same as `reachabilityGeoJSON` for each of the given altitudes, returned as a list of layers in the same order.
+    /// The risk of every location is only assessed and its runways are only cleared once for all altitudes. Range profiles
+    /// are reused by all altitudes which place a location at the same height, as happens with MSL altitudes. The polygons
+    /// and their unions depend on the altitude and are built for every layer.
+    #[wasm_bindgen(js_name = reachabilityGeoJSONForAltitudes)]
+    pub fn reachability_geojson_for_altitudes(
+        &self,
+        preferences: &Preferences,
+        location_map: &LocationMap,
+        aircraft: &Aircraft,
+        altitudes: Vec<f64>,
+        time: Option<String>,
+        bounds: Option<Box<[f64]>>,
+        envelopes: Option<bool>,
+        altitude_reference: AltitudeReference,
+        wind: Option<String>,
+    ) -> Result<String, JsValue> {
+        let time = Self::parse_time(time)?;
+        let wind = Self::parse_wind(wind)?;
+        let mut cache = ReachabilityCache::default();
+
+        let layers = altitudes
+            .into_iter()
+            .map(|altitude| {
+                let mut layer = self.reachability_at(
+                    preferences,
+                    location_map,
+                    aircraft,
+                    altitude,
+                    &time,
+                    bounds.as_deref(),
+                    envelopes.unwrap_or(false),
+                    altitude_reference,
+                    wind.as_ref(),
+                    &mut cache,
+                )?;
+
+                layer["altitude"] = altitude.into();
+                Ok(layer)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(serde_json::to_string(&layers).map_err(|e| e.to_string())?)
+    }
+
+    /// Same as `reachabilityGeoJSON` but for an aircraft which is flying the given heading (in degrees)
+    #[wasm_bindgen(js_name = reachabilityGeoJSONForHeading)]
+    pub fn reachability_geojson_for_heading(
+        &self,
+        preferences: &Preferences,
+        location_map: &LocationMap,
+        aircraft: &Aircraft,
+        altitude: f64,
+        heading: f64,
+        time: Option<String>,
+        bounds: Option<Box<[f64]>>,
+        altitude_reference: AltitudeReference,
+        wind: Option<String>,
+    ) -> Result<String, JsValue> {
+        let preferences = Preferences {
+            profile_mode: ProfileMode::Heading,
+            profile_heading: heading,
+            ..*preferences
+        };
+
+        self.reachability_geojson(
+            &preferences,
+            location_map,
+            aircraft,
+            altitude,
+            time,
+            bounds,
+            None,
+            altitude_reference,
+            wind,
+        )
+    }
+
+    #[wasm_bindgen(js_name = locationGeoJSON)]
+    pub fn location_geojson(
+        &self,
+        preferences: &Preferences,
+        location_map: &LocationMap,
+        aircraft: &Aircraft,
+        time: Option<String>,
+    ) -> Result<String, JsValue> {
+        let time = Self::parse_time(time)?;
+
+        let features = location_map
+            .locations()
+            .filter(|location| location.usage != UsageType::Aeronautical)
+            .map(|location| {
+                let mut line = match location.area() {
+                    // Areas are drawn as their outline
+                    Some(area) => MultiLineString(vec![area.exterior().clone()]),
+                    None => MultiLineString(vec![LineString::from(location.outline())]),
+                };
+
+                // If the location is NOT reversible, add a perpendicular dash at the end
+                // (to match the style of that one symbol airports can put out in case of radio failure :P)
+                if let Some(runway) = location.primary_runway().filter(|r| !r.reversible) {
+                    let bearing = runway.bearing();
+                    let stop_line_start = runway.end.haversine_destination(bearing + 90.0, 15.0).0;
+                    let stop_line_end = runway.end.haversine_destination(bearing - 90.0, 15.0).0;
+                    let stop_line = LineString(vec![stop_line_start, stop_line_end]);
+
+                    line.0.push(stop_line);
+                }
+
+                let mut properties = Map::new();
+                properties.insert(
+                    String::from("risk"),
+                    to_value(
+                        self.assess_risk_at(preferences, location, aircraft, &time)
+                            .overall,
+                    )
+                    .unwrap(),
+                );
+
+                Feature {
+                    bbox: None,
+                    geometry: Some((&line).into()),
+                    id: Some(Id::String(location.id())),
+                    properties: Some(properties),
+                    foreign_members: None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let geojson = GeoJson::FeatureCollection(FeatureCollection {
+            bbox: None,
+            features,
+            foreign_members: None,
+        });
+
+        Ok(geojson.to_string())
+    }
+
+    /// Glide paths to every reachable location, each ending where the aircraft crosses a threshold at screen height.
+    /// The altitude (in meters) is interpreted relative to the given reference.
+    #[wasm_bindgen(js_name = landingOptions)]
+    pub fn landing_options(
+        &self,
+        preferences: &Preferences,
+        latitude: f64,
+        longitude: f64,
+        heading: f64,
+        altitude: f64,
+        aircraft: &Aircraft,
+        locations: &LocationMap,
+        time: Option<String>,
+        altitude_reference: AltitudeReference,
+    ) -> Result<String, JsValue> {
+        let time = Self::parse_time(time)?;
+        let start = Point::new(longitude, latitude);
+
+        let mut options = self
+            .landing_paths(
+                preferences,
+                start,
+                heading,
+                altitude,
+                aircraft,
+                locations,
+                altitude_reference,
+            )
+            .into_iter()
+            .map(|(path, height_loss, margin, inset, location)| {
+                let assessment = self.assess_risk_at(preferences, location, aircraft, &time);
+
+                (path, height_loss, margin, inset, location, assessment)
+            })
+            .collect::<Vec<_>>();
+
+        // Rank the options by their risk score, preferring those with more height to spare
+        options.sort_by(|(_, _, margin_a, _, _, a), (_, _, margin_b, _, _, b)| {
+            a.score
+                .partial_cmp(&b.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| margin_b.partial_cmp(margin_a).unwrap_or(Ordering::Equal))
+        });
+
+        let features = options
+            .into_iter()
+            .enumerate()
+            .map(
+                |(rank, (path, height_loss, margin, inset, location, assessment)): (
+                    usize,
+                    (
+                        GeographicDubinPath,
+                        f64,
+                        f64,
+                        f64,
+                        &Location,
+                        RiskAssessment,
+                    ),
+                )| {
+                    let points = path.points().map(|p| p.0).collect::<Vec<_>>();
+                    let line = LineString(points);
+
+                    let mut properties = Map::new();
+                    properties.insert(String::from("rank"), to_value(rank).unwrap());
+                    properties.insert(String::from("risk"), to_value(assessment.overall).unwrap());
+                    properties.insert(String::from("score"), to_value(assessment.score).unwrap());
+                    properties.insert(String::from("heightLoss"), to_value(height_loss).unwrap());
+                    properties.insert(String::from("heightMargin"), to_value(margin).unwrap());
+                    properties.insert(String::from("inset"), to_value(inset).unwrap());
+                    properties.insert(
+                        String::from("reasons"),
+                        to_value(&assessment.reasons).unwrap(),
+                    );
+
+                    if let Some(ditching) = assessment.ditching {
+                        properties.insert(String::from("ditching"), to_value(ditching).unwrap());
+                    }
+
+                    Feature {
+                        bbox: None,
+                        geometry: Some((&line).into()),
+                        id: Some(Id::String(location.id())),
+                        properties: Some(properties),
+                        foreign_members: None,
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let geojson = GeoJson::FeatureCollection(FeatureCollection {
+            bbox: None,
+            features,
+            foreign_members: None,
+        });
+
+        Ok(geojson.to_string())
+    }
+
+    /// Splits a route into segments by the lowest risk of any location within glide reach. The route is given as
+    /// a GeoJSON LineString (or a feature thereof) and the altitude profile as `[distance, altitude]` pairs (in meters)
+    /// along it, which are interpolated in between and held beyond either end. Unsafe locations provide no coverage.
+    #[wasm_bindgen(js_name = routeCoverage)]
+    pub fn route_coverage(
+        &self,
+        preferences: &Preferences,
+        locations: &LocationMap,
         aircraft: &Aircraft,
-    ) -> String {
-        let features = location_map
-            .locations()
-            .filter(|location| location.usage != UsageType::Aeronautical)
-            .map(|location| {
-                let mut line =
-                    MultiLineString(vec![LineString(vec![location.start().0, location.end().0])]);
+        route: String,
+        altitude_profile: String,
+        time: Option<String>,
+        altitude_reference: AltitudeReference,
+    ) -> Result<String, JsValue> {
+        let time = Self::parse_time(time)?;
+        let route = Self::parse_route(&route)?;
+
+        let mut altitude_profile = serde_json::from_str::<Vec<(f64, f64)>>(&altitude_profile)
+            .map_err(|e| e.to_string())?;
+        altitude_profile.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let altitude_at =
+            |distance: f64| match altitude_profile.iter().position(|(d, _)| *d >= distance) {
+                Some(0) => Some(altitude_profile[0].1),
+                Some(i) => {
+                    let (low, high) = (altitude_profile[i - 1], altitude_profile[i]);
+                    let fraction = (distance - low.0) / (high.0 - low.0);
+                    Some(low.1 + (high.1 - low.1) * fraction)
+                }
+                None => altitude_profile.last().map(|(_, altitude)| *altitude),
+            };
+
+        // Step 1: Sample the route and determine the lowest risk among the reachable locations at each point
+        let mut assessments = HashMap::new();
+        let mut samples = Vec::new();
+
+        for (distance, position, heading) in Self::route_samples(&route) {
+            let altitude = altitude_at(distance).ok_or("Altitude profile is empty")?;
+
+            let mut coverage: Option<RiskClassification> = None;
+            let options = self.landing_paths(
+                preferences,
+                position,
+                heading,
+                altitude,
+                aircraft,
+                locations,
+                altitude_reference,
+            );
+
+            for (_, _, _, _, location) in options {
+                let risk = *assessments.entry(location.id()).or_insert_with(|| {
+                    self.assess_risk_at(preferences, location, aircraft, &time)
+                        .overall
+                });
+
+                let lower = coverage.map_or(true, |coverage| {
+                    Into::<u8>::into(risk) < Into::<u8>::into(coverage)
+                });
+
+                if risk != RiskClassification::Unsafe && lower {
+                    coverage = Some(risk);
+                }
+            }
 
-                // If the location is NOT reversible, add a perpendicular dash at the end
-                // (to match the style of that one symbol airports can put out in case of radio failure :P)
-                if !location.reversible {
-                    let bearing = location.bearing();
-                    let stop_line_start =
-                        location.end().haversine_destination(bearing + 90.0, 15.0).0;
-                    let stop_line_end =
-                        location.end().haversine_destination(bearing - 90.0, 15.0).0;
-                    let stop_line = LineString(vec![stop_line_start, stop_line_end]);
+            samples.push((distance, coverage));
+        }
 
-                    line.0.push(stop_line);
+        // Step 2: Join consecutive samples with the same coverage, segments change halfway between samples
+        let mut segments: Vec<(f64, f64, Option<RiskClassification>)> = Vec::new();
+        for (index, (distance, coverage)) in samples.iter().enumerate() {
+            match segments.last_mut() {
+                Some(segment) if segment.2 == *coverage => segment.1 = *distance,
+                Some(segment) => {
+                    let boundary = (samples[index - 1].0 + distance) / 2.0;
+                    segment.1 = boundary;
+                    segments.push((boundary, *distance, *coverage));
                 }
+                None => segments.push((0.0, *distance, *coverage)),
+            }
+        }
 
-                let mut properties = Map::new();
-                properties.insert(
-                    String::from("risk"),
-                    to_value(self.assess_risk(preferences, location, aircraft).overall).unwrap(),
+        // Step 3: Cut the route into the segments and summarise the coverage
+        let distances = route
+            .windows(2)
+            .scan(0.0, |travelled, segment| {
+                *travelled += segment[0].haversine_distance(&segment[1]);
+                Some(*travelled)
+            })
+            .collect::<Vec<_>>();
+        let travelled = distances.last().copied().unwrap_or(0.0);
+
+        let point_at = |distance: f64| {
+            let index = distances
+                .iter()
+                .position(|end| *end >= distance)
+                .unwrap_or(distances.len() - 1);
+            let start = if index == 0 {
+                0.0
+            } else {
+                distances[index - 1]
+            };
+            let fraction =
+                ((distance - start) / (distances[index] - start).max(f64::EPSILON)).clamp(0.0, 1.0);
+
+            route[index] + (route[index + 1] - route[index]) * fraction
+        };
+
+        let features = segments
+            .iter()
+            .map(|(start, end, coverage)| {
+                let vertices = distances
+                    .iter()
+                    .zip(route.iter().skip(1))
+                    .filter(|(distance, _)| *distance > start && *distance < end)
+                    .map(|(_, point)| point.0);
+                let line = LineString(
+                    std::iter::once(point_at(*start).0)
+                        .chain(vertices)
+                        .chain(std::iter::once(point_at(*end).0))
+                        .collect(),
                 );
 
+                let mut properties = Map::new();
+                properties.insert(String::from("coverage"), to_value(coverage).unwrap());
+                properties.insert(String::from("start"), to_value(start).unwrap());
+                properties.insert(String::from("end"), to_value(end).unwrap());
+                properties.insert(String::from("length"), to_value(end - start).unwrap());
+
                 Feature {
                     bbox: None,
                     geometry: Some((&line).into()),
-                    id: Some(Id::String(location.id())),
+                    id: None,
                     properties: Some(properties),
                     foreign_members: None,
                 }
             })
             .collect::<Vec<_>>();
 
-        let geojson = GeoJson::FeatureCollection(FeatureCollection {
-            bbox: None,
-            features,
-            foreign_members: None,
+        let total = travelled.max(f64::EPSILON);
+        let percentage = |coverage: Option<RiskClassification>| {
+            segments
+                .iter()
+                .filter(|segment| segment.2 == coverage)
+                .map(|(start, end, _)| end - start)
+                .fold(0.0, |sum, length| sum + length)
+                / total
+                * 100.0
+        };
+
+        let worst_gap = segments
+            .iter()
+            .filter(|(_, _, coverage)| coverage.is_none())
+            .max_by(|a, b| {
+                (a.1 - a.0)
+                    .partial_cmp(&(b.1 - b.0))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .map(|(start, end, _)| {
+                serde_json::json!({
+                    "start": start,
+                    "end": end,
+                    "length": end - start
+                })
+            });
+
+        let coverage = serde_json::json!({
+            "segments": GeoJson::FeatureCollection(FeatureCollection {
+                bbox: None,
+                features,
+                foreign_members: None,
+            }),
+            "coverage": {
+                "safe": percentage(Some(RiskClassification::Safe)),
+                "risky": percentage(Some(RiskClassification::Risky)),
+                "none": percentage(None)
+            },
+            "worstGap": worst_gap,
+            "length": travelled
         });
 
-        geojson.to_string()
+        Ok(serde_json::to_string(&coverage).map_err(|e| e.to_string())?)
     }
 
-    #[wasm_bindgen(js_name = landingOptions)]
-    pub fn landing_options(
+    /// Lowest altitude along a route from which a location of the given risk or less can be reached, either with
+    /// the worst initial heading or while flying along the route. Altitudes above the ceiling (in meters) are not
+    /// searched. Both are interpreted relative to the given reference.
+    #[wasm_bindgen(js_name = minimumAltitudeProfile)]
+    pub fn minimum_altitude_profile(
         &self,
         preferences: &Preferences,
-        latitude: f64,
-        longitude: f64,
-        heading: f64,
-        altitude: f64,
-        aircraft: &Aircraft,
         locations: &LocationMap,
-    ) -> String {
-        let start = Point::new(longitude, latitude);
-        let radius = Length::new::<meter>(aircraft.glide.turn_radius(preferences.bank));
+        aircraft: &Aircraft,
+        route: String,
+        risk: RiskClassification,
+        heading: RouteHeading,
+        ceiling: f64,
+        time: Option<String>,
+        altitude_reference: AltitudeReference,
+    ) -> Result<String, JsValue> {
+        let time = Self::parse_time(time)?;
+        let route = Self::parse_route(&route)?;
+        let mut cache = HashMap::new();
 
-        let features = locations
-            .locations()
-            .filter_map(|location| {
-                let mut points = vec![(location.start(), location.bearing())];
-                if location.reversible {
-                    points.push((location.end(), location.reverse_bearing()));
+        // Step 1: Find the location that requires the least altitude at each point along the route
+        let points = Self::route_samples(&route)
+            .into_iter()
+            .map(|(distance, position, track)| {
+                let minimum = self.minimum_altitude(
+                    preferences,
+                    locations,
+                    aircraft,
+                    position,
+                    match heading {
+                        RouteHeading::AlongTrack => Some(track),
+                        _ => None,
+                    },
+                    risk,
+                    ceiling,
+                    altitude_reference,
+                    &time,
+                    &mut cache,
+                );
+
+                MinimumAltitude {
+                    distance,
+                    altitude: minimum.map(|(altitude, _)| altitude),
+                    location: minimum.map(|(_, location)| location.id()),
                 }
+            })
+            .collect::<Vec<_>>();
 
-                points
-                    .into_iter()
-                    .flat_map(|(end, target_heading)| {
-                        let start_bearing = Angle::new::<degree>(heading);
-                        let end_bearing = Angle::new::<degree>(target_heading);
+        // Step 2: Render the profile, shading everything below the minimum altitude
+        let risk_color = "#FF3D00";
+        let route_color = "#2196F3";
 
-                        calculate_georeferenced_dubin_path_candidates(
-                            start,
-                            end,
-                            start_bearing,
-                            end_bearing,
-                            radius,
-                        )
-                    })
-                    .map(|path| {
-                        let height_loss = aircraft
-                            .glide
-                            .height_loss_over_geographic_path(&path, preferences.bank);
+        let width = points.last().map_or(0.0, |point| point.distance).max(1.0);
+        let height = points
+            .iter()
+            .filter_map(|point| point.altitude)
+            .fold(0.0, f64::max)
+            .max(1.0)
+            * 1.1;
+        let coordinates = |distance: f64, altitude: f64| (distance, height - altitude);
 
-                        (path, height_loss, location)
-                    })
-                    .filter(|(_, height_loss, _)| *height_loss < altitude)
-                    .min_by(|(_, height_loss_a, _), (_, height_loss_b, _)| {
-                        height_loss_a
-                            .partial_cmp(&height_loss_b)
-                            .unwrap_or(Ordering::Equal)
-                    })
-            })
-            .map(
-                |(path, height_loss, location): (GeographicDubinPath, f64, &Location)| {
-                    let points = path.points().map(|p| p.0).collect::<Vec<_>>();
-                    let line = LineString(points);
-                    let risk = self.assess_risk(preferences, location, aircraft).overall;
+        let mut graph = Document::new()
+            .set("viewBox", (0.0, 0.0, width, height))
+            .set("preserveAspectRatio", "none");
+
+        let mut index = 0;
+        while index < points.len() {
+            let run = points[index..]
+                .iter()
+                .take_while(|point| point.altitude.is_some() == points[index].altitude.is_some())
+                .collect::<Vec<_>>();
+            let (first, last) = (run[0], run[run.len() - 1]);
+
+            // Points from which no location is reachable below the ceiling are shaded entirely
+            let outline = match first.altitude {
+                Some(_) => run
+                    .iter()
+                    .fold(
+                        Data::new().move_to(coordinates(first.distance, 0.0)),
+                        |data, point| {
+                            data.line_to(coordinates(point.distance, point.altitude.unwrap_or(0.0)))
+                        },
+                    )
+                    .line_to(coordinates(last.distance, 0.0)),
+                None => Data::new()
+                    .move_to(coordinates(first.distance, 0.0))
+                    .line_to(coordinates(first.distance, height))
+                    .line_to(coordinates(last.distance, height))
+                    .line_to(coordinates(last.distance, 0.0)),
+            };
+
+            graph = graph.add(
+                Path::new()
+                    .set("fill", risk_color)
+                    .set("fill-opacity", "0.25")
+                    .set("stroke", "none")
+                    .set("d", outline.close()),
+            );
+
+            if first.altitude.is_some() {
+                let line = run.iter().skip(1).fold(
+                    Data::new().move_to(coordinates(first.distance, first.altitude.unwrap_or(0.0))),
+                    |data, point| {
+                        data.line_to(coordinates(point.distance, point.altitude.unwrap_or(0.0)))
+                    },
+                );
 
-                    let mut properties = Map::new();
-                    properties.insert(String::from("risk"), to_value(risk).unwrap());
-                    properties.insert(String::from("heightLoss"), to_value(height_loss).unwrap());
+                graph = graph.add(
+                    Path::new()
+                        .set("fill", "none")
+                        .set("stroke", route_color)
+                        .set("stroke-width", 2)
+                        .set("d", line),
+                );
+            }
 
-                    Feature {
-                        bbox: None,
-                        geometry: Some((&line).into()),
-                        id: Some(Id::String(location.id())),
-                        properties: Some(properties),
-                        foreign_members: None,
-                    }
-                },
-            )
-            .collect::<Vec<_>>();
+            index += run.len();
+        }
 
-        // TODO Also consider landing points that are partially into the runway (if long enough)
+        let profile = MinimumAltitudeProfile {
+            svg: Document::new().add(graph).to_string(),
+            points,
+        };
 
-        let geojson = GeoJson::FeatureCollection(FeatureCollection {
-            bbox: None,
-            features,
-            foreign_members: None,
-        });
+        Ok(serde_json::to_string(&profile).map_err(|e| e.to_string())?)
+    }
+
+    /// Lowest altitudes from which a safe or risky location can be reached with the worst initial heading, sampled
+    /// on a grid over the bounds [west, south, east, north]. Cells are roughly `resolution` meters wide and
+    /// locations above the `ceiling` (in meters) are not searched for.
+    #[wasm_bindgen(js_name = minimumAltitudeRaster)]
+    pub fn minimum_altitude_raster(
+        &self,
+        preferences: &Preferences,
+        locations: &LocationMap,
+        aircraft: &Aircraft,
+        bounds: Vec<f64>,
+        resolution: f64,
+        ceiling: f64,
+        time: Option<String>,
+        altitude_reference: AltitudeReference,
+    ) -> Result<AltitudeRaster, JsValue> {
+        let time = Self::parse_time(time)?;
+        let mut cache = HashMap::new();
+
+        let (west, south, east, north) = match bounds.as_slice() {
+            &[west, south, east, north] => (west, south, east, north),
+            _ => return Err("Bounds must consist of [west, south, east, north]".into()),
+        };
 
-        geojson.to_string()
+        // Cells span the same distance in both directions at the center of the bounds
+        let center = Point::new((west + east) / 2.0, (south + north) / 2.0);
+        let dx = center.haversine_destination(90.0, resolution).x() - center.x();
+        let dy = center.haversine_destination(0.0, resolution).y() - center.y();
+
+        let raster =
+            AltitudeRaster::sample((west, south, east, north), (dx, dy), ceiling, |point| {
+                self.minimum_altitude(
+                    preferences,
+                    locations,
+                    aircraft,
+                    point,
+                    None,
+                    RiskClassification::Risky,
+                    ceiling,
+                    altitude_reference,
+                    &time,
+                    &mut cache,
+                )
+                .map(|(altitude, _)| altitude)
+            })?;
+
+        Ok(raster)
     }
 
     #[wasm_bindgen(js_name = takeoffProfile)]
@@ -702,6 +2244,25 @@ impl Calculator {
     }
 }
 
+#[wasm_bindgen]
+impl RiskAssessment {
+    /// Reasons for the assessment as JSON, each with a code and where applicable the value and threshold
+    #[wasm_bindgen(js_name = reasons)]
+    pub fn serialize_reasons(&self) -> Result<String, JsValue> {
+        Ok(serde_json::to_string(&self.reasons).map_err(|e| e.to_string())?)
+    }
+}
+
+impl Preferences {
+    fn default_profile_rays() -> usize {
+        36
+    }
+
+    fn default_profile_mode() -> ProfileMode {
+        ProfileMode::WorstCase
+    }
+}
+
 #[wasm_bindgen]
 impl Preferences {
     #[wasm_bindgen(constructor)]
@@ -713,3 +2274,751 @@ impl Preferences {
         Ok(serde_json::to_string(&self).map_err(|e| e.to_string())?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AIRCRAFT: &str = "
+        id: C172
+        name: Cessna 172
+        mtow: 2450
+        takeoff: { groundRoll: 945, totalDistance: 1685, speed: 57 }
+        climb: { rate: 720, speed: 79 }
+        glide: { distance: 1.5, speed: 65 }
+        landing: { groundRoll: 550, totalDistance: 1295, speed: 62, descentRate: 1500 }
+    ";
+
+    const PREFERENCES: &str = r#"{
+        "bank": 0.78,
+        "epsilon": 0.1,
+        "riskyLandingHeadroom": -0.05,
+        "unsafeLandingHeadroom": -0.15,
+        "eventLocationClassification": "risky",
+        "denselyCrowdedClassification": "unsafe",
+        "profileRays": 72
+    }"#;
+
+    /// Profiles with the worst, the best and a fixed initial heading
+    fn profiles(altitude: f64) -> [AircraftRangeProfile; 3] {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences: Preferences = serde_json::from_str(PREFERENCES).unwrap();
+        let calculator = Calculator::new();
+
+        [
+            InitialHeading::Worst,
+            InitialHeading::Best,
+            InitialHeading::Fixed(1.0),
+        ]
+        .map(|initial_heading| {
+            calculator.aircraft_range_profile(&preferences, &aircraft, altitude, initial_heading)
+        })
+    }
+
+    /// Distance of each point of the profile from the threshold
+    fn ranges(profile: &AircraftRangeProfile) -> Vec<f64> {
+        let origin = Point::new(0.0, 0.0);
+
+        profile
+            .0
+            .iter()
+            .map(|point| point.euclidean_distance(&origin))
+            .collect()
+    }
+
+    /// Profile as it was calculated before the full circle has been considered: rays tangent to the approach circle
+    /// up to 170º with a fixed heading along the ray, mirrored to the other side and joined by a straight line.
+    fn approximated_profile(
+        preferences: &Preferences,
+        aircraft: &Aircraft,
+        altitude: f64,
+    ) -> Vec<Point<f64>> {
+        let maximum_range = aircraft.glide.ratio() * altitude * 2.0;
+        let circle_radius = aircraft.glide.turn_radius(preferences.bank);
+        let circle_origin = Point::new(-circle_radius, 0.0);
+        let origin = Point::new(0.0, 0.0);
+        let radius = Length::new::<meter>(circle_radius);
+
+        let half = (0..18)
+            .map(|step| {
+                let angle = (-(step as f64) * 10.0).to_radians();
+                let ray_origin = circle_origin
+                    + Point::new(circle_radius * angle.cos(), circle_radius * angle.sin());
+                let ray = angle - FRAC_PI_2;
+
+                let distance = binary_search(0.0, maximum_range, preferences.epsilon, |range| {
+                    let target = ray_origin + Point::new(range * ray.cos(), range * ray.sin());
+
+                    calculate_dubin_path_candidates(
+                        target,
+                        origin,
+                        Angle::new::<radian>(ray),
+                        Angle::new::<degree>(90.0),
+                        radius,
+                    )
+                    .iter()
+                    .map(|path| {
+                        aircraft
+                            .glide
+                            .height_loss_over_geometric_path(path, preferences.bank)
+                    })
+                    .fold(f64::INFINITY, f64::min)
+                        <= altitude
+                });
+
+                ray_origin + Point::new(distance * ray.cos(), distance * ray.sin())
+            })
+            .collect::<Vec<_>>();
+
+        let mirrored = half.iter().rev().map(|p| Point::new(-p.x(), p.y()));
+        half.iter().copied().chain(mirrored).collect()
+    }
+
+    /// Distance from the origin at which a ray in the given direction leaves the polygon
+    fn radial_extent(polygon: &[Point<f64>], angle: f64) -> f64 {
+        let direction = Point::new(angle.cos(), angle.sin());
+
+        polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .filter_map(|(a, b)| {
+                let edge = *b - *a;
+                let denominator = direction.x() * edge.y() - direction.y() * edge.x();
+
+                if denominator.abs() < 1e-12 {
+                    return None;
+                }
+
+                let distance = (a.x() * edge.y() - a.y() * edge.x()) / denominator;
+                let fraction = (a.x() * direction.y() - a.y() * direction.x()) / denominator;
+
+                (distance >= 0.0 && (0.0..=1.0).contains(&fraction)).then(|| distance)
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn worst_case_profile_lies_inside_best_case() {
+        for altitude in [150.0, 300.0, 600.0, 1200.0] {
+            let [worst, best, fixed] = profiles(altitude);
+            let best = ranges(&best);
+
+            for other in [worst, fixed] {
+                for (range, best) in ranges(&other).into_iter().zip(&best) {
+                    assert!(
+                        range <= best + 0.1,
+                        "{} beyond {} at {}m",
+                        range,
+                        best,
+                        altitude
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn profile_lies_within_glide_range() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+
+        for altitude in [60.0, 300.0, 1200.0] {
+            for profile in profiles(altitude) {
+                let maximum_range = aircraft.glide.ratio() * altitude;
+
+                assert!(ranges(&profile).iter().all(|range| *range <= maximum_range));
+            }
+        }
+    }
+
+    #[test]
+    fn profile_is_symmetric_about_runway() {
+        for altitude in [300.0, 1200.0] {
+            let [worst, best, _] = profiles(altitude);
+
+            for profile in [worst, best] {
+                // Rays start at the approach, which lies on the axis of symmetry
+                let points = &profile.0[1..];
+                for (point, mirrored) in points.iter().zip(points.iter().rev()) {
+                    assert!(
+                        (point.x() + mirrored.x()).abs() < 0.1
+                            && (point.y() - mirrored.y()).abs() < 0.1,
+                        "{:?} does not mirror {:?} at {}m",
+                        point,
+                        mirrored,
+                        altitude
+                    );
+                }
+            }
+        }
+    }
+
+    /// Measures how far the approximated profile deviated from the exact one. At low altitudes it overestimated
+    /// the range towards the approach by up to ~690m while missing everything beyond the threshold, at higher
+    /// altitudes it stays within a few meters on the approach but underestimates the range alongside the runway.
+    #[test]
+    fn approximated_range_profile_error() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences: Preferences = serde_json::from_str(PREFERENCES).unwrap();
+        let calculator = Calculator::new();
+
+        // Altitude, largest overestimation, largest underestimation and mean absolute error (all in meters)
+        let expectations = [
+            (300.0, 688.4, 443.0, 290.5),
+            (600.0, 14.6, 71.8, 9.4),
+            (1200.0, 5.3, 129.0, 23.4),
+        ];
+
+        for (altitude, overestimation, underestimation, mean) in expectations {
+            let exact = calculator.aircraft_range_profile(
+                &preferences,
+                &aircraft,
+                altitude,
+                InitialHeading::Worst,
+            );
+            let approximation = approximated_profile(&preferences, &aircraft, altitude);
+
+            let errors = exact
+                .0
+                .iter()
+                .map(|point| {
+                    let angle = point.y().atan2(point.x());
+                    radial_extent(&approximation, angle)
+                        - point.euclidean_distance(&Point::new(0.0, 0.0))
+                })
+                .collect::<Vec<_>>();
+
+            let measured = (
+                errors.iter().copied().fold(0.0, f64::max),
+                errors.iter().map(|e| -e).fold(0.0, f64::max),
+                errors.iter().map(|e| e.abs()).sum::<f64>() / errors.len() as f64,
+            );
+
+            assert!(
+                (measured.0 - overestimation).abs() < 1.0
+                    && (measured.1 - underestimation).abs() < 1.0
+                    && (measured.2 - mean).abs() < 1.0,
+                "approximation error at {}m changed: {:?}",
+                altitude,
+                measured
+            );
+        }
+    }
+
+    #[test]
+    fn profile_path_matches_dubins_path() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences: Preferences = serde_json::from_str(PREFERENCES).unwrap();
+        let calculator = Calculator::new();
+        let radius = aircraft.glide.turn_radius(preferences.bank);
+
+        // Heading east from one radius left of the approach, a quarter turn to the left ends on the threshold
+        let (_, distance) = calculator.profile_path(
+            &preferences,
+            &aircraft,
+            Point::new(-radius, -radius),
+            InitialHeading::Fixed(0.0),
+            f64::INFINITY,
+        );
+        assert!((distance - FRAC_PI_2 * radius).abs() < 1e-6);
+
+        // Beside the threshold the worst heading only just allows to reach it, as checked over the full circle
+        let [worst, _, _] = profiles(600.0);
+        let point = worst.0[18];
+        let worst_loss = (0..3600)
+            .map(|i| (i as f64 / 10.0).to_radians())
+            .map(|heading| {
+                calculate_dubin_path_candidates(
+                    point,
+                    Point::new(0.0, 0.0),
+                    Angle::new::<radian>(heading),
+                    Angle::new::<degree>(90.0),
+                    Length::new::<meter>(radius),
+                )
+                .iter()
+                .map(|path| {
+                    aircraft
+                        .glide
+                        .height_loss_over_geometric_path(path, preferences.bank)
+                })
+                .fold(f64::INFINITY, f64::min)
+            })
+            .fold(0.0, f64::max);
+
+        assert!(point.y().abs() < 1e-6 && point.x() > 0.0);
+        assert!((worst_loss - 600.0).abs() < 1.0, "{}", worst_loss);
+    }
+
+    #[test]
+    fn msl_altitudes_share_range_profiles() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences = Preferences {
+            profile_rays: 8,
+            ..serde_json::from_str(PREFERENCES).unwrap()
+        };
+        let locations = crate::Parser::new()
+            .parse_locations(include_str!("../../static/data/locations.yml"))
+            .ok()
+            .unwrap();
+        let calculator = Calculator::new();
+        let time = DateTime::parse_from_rfc3339("2022-01-15T12:00:00+01:00").unwrap();
+
+        // Locations at 82ft and 119ft lie at the same height below either altitude
+        let reachability = |altitude: f64, cache: &mut ReachabilityCache| {
+            calculator
+                .reachability_at(
+                    &preferences,
+                    &locations,
+                    &aircraft,
+                    Length::new::<foot>(altitude).get::<meter>(),
+                    &time,
+                    None,
+                    false,
+                    AltitudeReference::MSL,
+                    None,
+                    cache,
+                )
+                .unwrap()
+        };
+
+        let mut separate = 0;
+        for altitude in [2000.0, 2037.0] {
+            let mut cache = ReachabilityCache::default();
+            reachability(altitude, &mut cache);
+            separate += cache.profiles.len();
+        }
+
+        let mut cache = ReachabilityCache::default();
+        for altitude in [2000.0, 2037.0] {
+            let layer = reachability(altitude, &mut cache);
+            assert!(!layer["byID"].as_object().unwrap().is_empty());
+        }
+
+        assert!(cache.profiles.len() < separate);
+    }
+
+    #[test]
+    fn resurvey_report_follows_staleness_rules() {
+        let preferences: Preferences = serde_json::from_str(PREFERENCES).unwrap();
+        let location_map = crate::Parser::new()
+            .parse_locations(include_str!("../../static/data/locations.yml"))
+            .unwrap();
+        let mut calculator = Calculator::new();
+        let staleness = |calculator: &Calculator| {
+            let report = calculator
+                .resurvey_report(
+                    &preferences,
+                    &location_map,
+                    Some(String::from("2030-01-01T00:00:00+00:00")),
+                )
+                .unwrap();
+
+            serde_json::from_str::<Vec<serde_json::Value>>(&report)
+                .unwrap()
+                .into_iter()
+                .map(|entry| {
+                    let id = entry["id"].as_str().unwrap().to_string();
+                    (location_map.get(id).unwrap(), entry["staleness"].clone())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Without survey ages in the preferences the default rules never consider a survey outdated
+        assert!(staleness(&calculator)
+            .iter()
+            .all(|(_, staleness)| staleness == "safe"));
+
+        calculator
+            .set_rules(Some(String::from(
+                "
+                - name: Airfields change quickly
+                  code: SURVEY_AGE_ABOVE_AIRFIELD
+                  factor: staleness
+                  classification: unsafe
+                  when:
+                    usage: [Aeronautical]
+                    surveyAgeAbove: 0
+                ",
+            )))
+            .unwrap();
+
+        for (location, staleness) in staleness(&calculator) {
+            let expected = if location.usage == UsageType::Aeronautical {
+                "unsafe"
+            } else {
+                "safe"
+            };
+
+            assert_eq!(staleness, expected, "{}", location.name);
+        }
+    }
+
+    /// Location on water with the given ditching characteristics, if any
+    fn water_location(ditching: &str) -> Location {
+        serde_yaml::from_str(&format!(
+            "
+            name: Außenalster
+            elevation: 9
+            reversible: true
+            surface: Water
+            usage: Waterway
+            coordinates:
+              start: [53.57388927668382, 10.006789650350846]
+              end: [53.55869625363589, 9.997048417203736]
+            {}
+            ",
+            ditching
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn ditching_assessment_by_sea_state_and_rescue() {
+        use RiskClassification::*;
+
+        let calculator = Calculator::new();
+        let expectations = [
+            ("Calm", "OnSite", Safe, Safe, Risky),
+            ("Calm", "Nearby", Safe, Safe, Risky),
+            ("Calm", "Remote", Safe, Risky, Risky),
+            ("Moderate", "OnSite", Risky, Safe, Risky),
+            ("Moderate", "Nearby", Risky, Safe, Risky),
+            ("Moderate", "Remote", Risky, Risky, Risky),
+            ("Rough", "OnSite", Unsafe, Safe, Unsafe),
+            ("Rough", "Nearby", Unsafe, Safe, Unsafe),
+            ("Rough", "Remote", Unsafe, Risky, Unsafe),
+        ];
+
+        for (sea_state, rescue, sea_state_risk, rescue_risk, overall) in expectations {
+            let location = water_location(&format!(
+                "ditching: {{ width: 200, shoreDistance: 50, seaState: {}, rescue: {} }}",
+                sea_state, rescue
+            ));
+            let assessment = calculator.assess_ditching(&location).unwrap();
+
+            assert_eq!(assessment.width, Safe);
+            assert_eq!(assessment.shore, Safe);
+            assert_eq!(assessment.sea_state, sea_state_risk, "{}", sea_state);
+            assert_eq!(assessment.rescue, rescue_risk, "{}", rescue);
+            assert_eq!(
+                assessment.overall, overall,
+                "{} with {} rescue",
+                sea_state, rescue
+            );
+        }
+    }
+
+    #[test]
+    fn ditching_assessment_without_characteristics() {
+        let calculator = Calculator::new();
+
+        // Water without ditching characteristics can not be graded, neither can locations on land
+        assert!(calculator.assess_ditching(&water_location("")).is_none());
+
+        let mut location = water_location(
+            "ditching: { width: 200, shoreDistance: 50, seaState: Calm, rescue: OnSite }",
+        );
+        location.surface = SurfaceType::Gras;
+        assert!(calculator.assess_ditching(&location).is_none());
+    }
+
+    #[test]
+    fn headroom_reasons_of_short_field() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences: Preferences = serde_json::from_str(PREFERENCES).unwrap();
+        // About 300m of grass, too short for the landing distance of the aircraft
+        let location: Location = serde_yaml::from_str(
+            "
+            name: Kurze Wiese
+            elevation: 30
+            reversible: true
+            surface: Gras
+            usage: Agricultural
+            humanPresence: Unlikely
+            coordinates:
+              start: [53.5, 10.0]
+              end: [53.5, 10.0045]
+            surveyDate: 2021-11-21T15:42:56+0000
+            ",
+        )
+        .unwrap();
+        let headroom = location.landing_headroom(&aircraft);
+
+        let assessment = Calculator::new().assess_risk_at(
+            &preferences,
+            &location,
+            &aircraft,
+            &Calculator::parse_time(Some(String::from("2022-06-01T12:00:00+02:00"))).unwrap(),
+        );
+
+        // Only the most severe reason of the factor is reported
+        assert!(headroom < -0.15);
+        assert_eq!(assessment.headroom, RiskClassification::Unsafe);
+        assert_eq!(
+            serde_json::to_value(&assessment.reasons).unwrap(),
+            serde_json::json!([{
+                "code": "HEADROOM_BELOW_UNSAFE",
+                "factor": "headroom",
+                "classification": "unsafe",
+                "value": headroom,
+                "threshold": -0.15
+            }])
+        );
+    }
+
+    /// One-way line of about 800m towards the north-east
+    fn drift_location() -> Location {
+        serde_yaml::from_str(
+            "
+            name: Lange Wiese
+            elevation: 30
+            reversible: false
+            surface: Asphalt
+            usage: Aeronautical
+            coordinates:
+              start: [53.5, 10.0]
+              end: [53.505, 10.008]
+            surveyDate: 2021-11-21T15:42:56+0000
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn calm_wind_leaves_profile_unchanged() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let location = drift_location();
+        let runway = location.runways()[0];
+        let [_, profile, _] = profiles(500.0);
+        let inset = location.runway_inset(&runway, &aircraft).max(0.0);
+
+        let drifted = Calculator::new().location_range_profile(
+            &location,
+            &runway,
+            &aircraft,
+            &profile,
+            Point::new(0.0, 0.0),
+        );
+
+        for (point, drifted) in profile.0.iter().zip(drifted.0.iter()) {
+            let expected = if point.y() > 0.0 {
+                Point::new(point.x(), point.y() + inset)
+            } else {
+                *point
+            };
+
+            assert_eq!(*drifted, expected);
+        }
+    }
+
+    #[test]
+    fn constant_wind_moves_profile_upwind() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let location = drift_location();
+        let runway = location.runways()[0];
+        let [_, profile, _] = profiles(500.0);
+        let calculator = Calculator::new();
+        let speed = aircraft.glide.speed().get::<meter_per_second>();
+
+        for direction in [0.0, 110.0, 250.0].iter() {
+            let wind = crate::Wind {
+                direction: *direction,
+                speed: 20.0,
+            };
+            let polygon = |wind: Point<f64>| {
+                calculator.runway_range_polygon(&location, &runway, &aircraft, &profile, wind)
+            };
+            let (calm, drifted) = (polygon(Point::new(0.0, 0.0)), polygon(wind.velocity()));
+            let wind_speed = wind.velocity().euclidean_distance(&Point::new(0.0, 0.0));
+
+            // Every point is moved towards the direction the wind is coming from, by the distance
+            // the air mass travels while flying from there to the threshold
+            for ((calm, drifted), distance) in calm
+                .exterior()
+                .points_iter()
+                .zip(drifted.exterior().points_iter())
+                .zip(profile.1.iter())
+            {
+                let expected = wind_speed * distance / speed;
+                let drift = calm.haversine_distance(&drifted);
+
+                assert!(
+                    (drift - expected).abs() < 1.0 + expected * 0.01,
+                    "drifted {} instead of {}",
+                    drift,
+                    expected
+                );
+
+                if expected > 1.0 {
+                    let bearing = calm.bearing(drifted);
+                    assert!((bearing - direction).to_radians().cos() > 0.999);
+                }
+            }
+        }
+    }
+
+    /// East-west runway of about 1km starting at the given longitude
+    fn airfield(longitude: f64, human_presence: &str) -> Location {
+        serde_yaml::from_str(&format!(
+            "
+            name: Feld
+            elevation: 30
+            reversible: true
+            surface: Asphalt
+            usage: Aeronautical
+            humanPresence: {}
+            coordinates:
+              start: [53.5, {}]
+              end: [53.5, {}]
+            surveyDate: 2021-11-21T15:42:56+0000
+            ",
+            human_presence,
+            longitude,
+            longitude + 0.015
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn route_coverage_reports_uncovered_legs() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences: Preferences = serde_json::from_str(PREFERENCES).unwrap();
+        let route = r#"{"type": "LineString", "coordinates": [[9.98, 53.49], [10.43, 53.49]]}"#;
+        let expectations = [
+            ("EventOnly", Some(RiskClassification::Risky)),
+            ("Dense", None),
+        ];
+
+        // A safe field at the start and another one about 26km further east, too far apart to glide
+        // from one into the reach of the other at 300m
+        for (presence, coverage) in expectations.iter() {
+            let locations =
+                LocationMap::new(vec![airfield(10.0, "Unlikely"), airfield(10.4, presence)]);
+
+            let result = Calculator::new()
+                .route_coverage(
+                    &preferences,
+                    &locations,
+                    &aircraft,
+                    route.to_string(),
+                    "[[0, 300]]".to_string(),
+                    Some(String::from("2022-06-01T12:00:00+02:00")),
+                    AltitudeReference::AGL,
+                )
+                .unwrap();
+            let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+            let segments = result["segments"]["features"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|feature| {
+                    let properties = &feature["properties"];
+                    (
+                        properties["start"].as_f64().unwrap(),
+                        properties["end"].as_f64().unwrap(),
+                        serde_json::from_value::<Option<RiskClassification>>(
+                            properties["coverage"].clone(),
+                        )
+                        .unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let length = result["length"].as_f64().unwrap();
+
+            assert_eq!(segments[0].2, Some(RiskClassification::Safe));
+            assert_eq!(segments[1].2, None);
+            assert_eq!(segments[segments.len() - 1].2, *coverage);
+            assert_eq!(segments[0].0, 0.0);
+            assert!((segments[segments.len() - 1].1 - length).abs() < 1e-6);
+            for pair in segments.windows(2) {
+                assert_eq!(pair[0].1, pair[1].0);
+                assert_ne!(pair[0].2, pair[1].2);
+            }
+
+            // The worst gap is the longest uncovered leg, between the two fields
+            let gap = segments
+                .iter()
+                .filter(|(_, _, coverage)| coverage.is_none())
+                .max_by(|a, b| (a.1 - a.0).partial_cmp(&(b.1 - b.0)).unwrap())
+                .unwrap();
+            assert_eq!(result["worstGap"]["start"].as_f64().unwrap(), gap.0);
+            assert_eq!(result["worstGap"]["end"].as_f64().unwrap(), gap.1);
+            assert!(gap.0 > 0.0 && gap.1 - gap.0 > 10000.0);
+
+            let percentages = ["safe", "risky", "none"]
+                .iter()
+                .map(|key| result["coverage"][key].as_f64().unwrap())
+                .collect::<Vec<_>>();
+            assert!((percentages.iter().sum::<f64>() - 100.0).abs() < 1e-6);
+            assert!(percentages[0] > 0.0 && percentages[2] > 0.0);
+            assert_eq!(percentages[1] > 0.0, coverage.is_some());
+        }
+    }
+
+    #[test]
+    fn minimum_altitude_decreases_towards_safe_field() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences: Preferences = serde_json::from_str(PREFERENCES).unwrap();
+        let safe = airfield(10.0, "Unlikely");
+
+        // Straight in from 13km west until short final, passing an unsafe field on the way
+        let locations = LocationMap::new(vec![safe.clone(), airfield(9.82, "Dense")]);
+        let route = r#"{"type": "LineString", "coordinates": [[9.8, 53.5], [9.99, 53.5]]}"#;
+        let ceiling = 600.0;
+
+        let [along_track, worst_case] =
+            [RouteHeading::AlongTrack, RouteHeading::WorstCase].map(|heading| {
+                let profile = Calculator::new()
+                    .minimum_altitude_profile(
+                        &preferences,
+                        &locations,
+                        &aircraft,
+                        route.to_string(),
+                        RiskClassification::Risky,
+                        heading,
+                        ceiling,
+                        Some(String::from("2022-06-01T12:00:00+02:00")),
+                        AltitudeReference::AGL,
+                    )
+                    .unwrap();
+
+                serde_json::from_str::<MinimumAltitudeProfile>(&profile)
+                    .unwrap()
+                    .points
+            });
+
+        for points in [&along_track, &worst_case].iter() {
+            // Out of reach below the ceiling at the start, reachable by the end
+            assert_eq!(points[0].altitude, None);
+            assert!(points[points.len() - 1].altitude.is_some());
+
+            for point in points.iter() {
+                if let Some(altitude) = point.altitude {
+                    assert!(altitude <= ceiling);
+                    assert_eq!(point.location, Some(safe.id()));
+                }
+            }
+        }
+
+        // Flying straight towards the field the required altitude only ever drops, while turning back
+        // from a heading away from it can never require less
+        for pair in along_track.windows(2) {
+            match (pair[0].altitude, pair[1].altitude) {
+                (Some(before), Some(after)) => assert!(after <= before + 1e-6),
+                (Some(_), None) => panic!("Lost reach at {}m", pair[1].distance),
+                _ => {}
+            }
+        }
+
+        for (along_track, worst_case) in along_track.iter().zip(worst_case.iter()) {
+            match (along_track.altitude, worst_case.altitude) {
+                (Some(along_track), Some(worst_case)) => assert!(worst_case >= along_track - 1e-6),
+                (None, Some(_)) => panic!(
+                    "Only reachable with the worst heading at {}m",
+                    along_track.distance
+                ),
+                _ => {}
+            }
+        }
+    }
+}
@@ -15,6 +15,8 @@ use wasm_bindgen::prelude::*;
 
 const TURN_AIRSPEED_SAFETY_FACTOR: f64 = 1.5;
 const SPECIFIC_GRAVITY: f64 = 9.81;
+/// Height (in feet) above the threshold from which the total landing distance is measured
+pub(crate) const SCREEN_HEIGHT: f64 = 50.0;
 
 #[wasm_bindgen]
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -29,6 +31,11 @@ pub struct Aircraft {
     #[serde(rename = "mtow")]
     pub raw_mtow: usize,
 
+    /// Free-form class of aircraft (e.g. `SEP`) which risk rules may refer to
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
     pub takeoff: TakeoffPerformance,
     pub climb: ClimbPerformance,
     pub glide: GlidePerformance,
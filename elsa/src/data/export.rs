@@ -0,0 +1,282 @@
+use crate::{Aircraft, Calculator, Location, LocationMap, Preferences, RiskClassification};
+use chrono::{DateTime, FixedOffset};
+use std::collections::HashSet;
+use strum::IntoEnumIterator;
+use uom::si::{
+    f64::Length,
+    length::{foot, meter},
+};
+use wasm_bindgen::prelude::*;
+
+/// Maximum length of waypoint identifiers accepted by most EFBs and avionics
+const WAYPOINT_IDENT_LENGTH: usize = 10;
+
+#[wasm_bindgen]
+impl LocationMap {
+    /// KML document with one line per location, colored by its risk for the given aircraft
+    #[wasm_bindgen(js_name = "exportKML")]
+    pub fn export_kml_at(
+        &self,
+        calculator: &Calculator,
+        preferences: &Preferences,
+        aircraft: &Aircraft,
+        time: Option<String>,
+    ) -> Result<String, JsValue> {
+        let time = Calculator::parse_time(time)?;
+
+        Ok(self.export_kml(calculator, preferences, aircraft, &time))
+    }
+
+    /// GPX document with a waypoint at every threshold
+    #[wasm_bindgen(js_name = "exportGPX")]
+    pub fn export_gpx(&self, aircraft: &Aircraft) -> String {
+        let mut gpx = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <gpx version=\"1.1\" creator=\"E.L.S.A.\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n",
+        );
+
+        for location in self.sorted_locations() {
+            let elevation = Length::new::<foot>(location.elevation as f64).get::<meter>();
+
+            let directions = location
+                .primary_runway()
+                .map(|runway| runway.landing_directions())
+                .unwrap_or_default();
+
+            for (threshold, bearing) in directions {
+                let bearing = normalize_bearing(bearing);
+                let name = format!("{} {:03.0}", location.name, bearing);
+                let description = format!(
+                    "Landing bearing {:03.0}°. {}",
+                    bearing,
+                    summary(location, aircraft)
+                );
+
+                gpx.push_str(&format!(
+                    "  <wpt lat=\"{}\" lon=\"{}\">\n    \
+                         <ele>{:.1}</ele>\n    \
+                         <name>{}</name>\n    \
+                         <desc>{}</desc>\n    \
+                         <sym>Waypoint</sym>\n  \
+                       </wpt>\n",
+                    threshold.lat(),
+                    threshold.lng(),
+                    elevation,
+                    escape_xml(&name),
+                    escape_xml(&description)
+                ));
+            }
+        }
+
+        gpx.push_str("</gpx>\n");
+        gpx
+    }
+
+    /// User waypoint CSV (`Name,Description,Latitude,Longitude`) with a waypoint at every threshold,
+    /// as understood by common EFBs like ForeFlight and Garmin Pilot
+    #[wasm_bindgen(js_name = "exportCSV")]
+    pub fn export_csv(&self, aircraft: &Aircraft) -> String {
+        let mut csv = String::from("Name,Description,Latitude,Longitude\n");
+        let mut idents = HashSet::new();
+
+        for location in self.sorted_locations() {
+            let directions = location
+                .primary_runway()
+                .map(|runway| runway.landing_directions())
+                .unwrap_or_default();
+
+            for (threshold, bearing) in directions {
+                let bearing = normalize_bearing(bearing);
+                let description = format!(
+                    "{} {:03.0}. {}",
+                    location.name,
+                    bearing,
+                    summary(location, aircraft)
+                );
+
+                csv.push_str(&format!(
+                    "{},{},{:.6},{:.6}\n",
+                    waypoint_ident(&location.name, bearing, &mut idents),
+                    escape_csv(&description),
+                    threshold.lat(),
+                    threshold.lng()
+                ));
+            }
+        }
+
+        csv
+    }
+}
+
+impl LocationMap {
+    /// Locations in a stable order so that subsequent exports can be compared
+    fn sorted_locations(&self) -> Vec<&Location> {
+        let mut locations = self.locations().collect::<Vec<_>>();
+        locations.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id().cmp(&b.id())));
+        locations
+    }
+
+    pub fn export_kml(
+        &self,
+        calculator: &Calculator,
+        preferences: &Preferences,
+        aircraft: &Aircraft,
+        time: &DateTime<FixedOffset>,
+    ) -> String {
+        let mut kml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n\
+             <Document>\n  \
+               <name>E.L.S.A. emergency landing sites ({})</name>\n",
+            escape_xml(&aircraft.name)
+        );
+
+        // KML colors are noted as aabbggrr
+        for risk in RiskClassification::iter() {
+            let color = match risk {
+                RiskClassification::Safe => "ff3c8e38",
+                RiskClassification::Risky => "ff07c1ff",
+                RiskClassification::Unsafe => "ff194ae6",
+                // Skip the variant wasm-bindgen adds for invalid values
+                _ => continue,
+            };
+
+            kml.push_str(&format!(
+                "  <Style id=\"{:?}\">\n    \
+                     <LineStyle><color>{}</color><width>4</width></LineStyle>\n  \
+                   </Style>\n",
+                risk, color
+            ));
+        }
+
+        for location in self.sorted_locations() {
+            let risk = calculator
+                .assess_risk_at(preferences, location, aircraft, time)
+                .overall;
+
+            // Areas are drawn as their outline
+            let mut points = location.outline();
+            if location.area().is_some() {
+                points.extend(points.first().copied());
+            }
+
+            let coordinates = points
+                .iter()
+                .map(|p| format!("{},{},0", p.lng(), p.lat()))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let description = format!("Risk: {:?}. {}", risk, summary(location, aircraft));
+
+            kml.push_str(&format!(
+                "  <Placemark>\n    \
+                     <name>{}</name>\n    \
+                     <description>{}</description>\n    \
+                     <styleUrl>#{:?}</styleUrl>\n    \
+                     <LineString><tessellate>1</tessellate><coordinates>{}</coordinates></LineString>\n  \
+                   </Placemark>\n",
+                escape_xml(&location.name),
+                escape_xml(&description),
+                risk,
+                coordinates
+            ));
+        }
+
+        kml.push_str("</Document>\n</kml>\n");
+        kml
+    }
+}
+
+/// Key facts about a location that are part of every export
+fn summary(location: &Location, aircraft: &Aircraft) -> String {
+    let mut summary = format!(
+        "Surface: {:?}, Length: {:.0}m, Headroom: {:.0}%",
+        location.surface,
+        location.length(),
+        location.landing_headroom(aircraft) * 100.0
+    );
+
+    if let Some(remarks) = &location.remarks {
+        summary.push_str(&format!(", Remarks: {}", remarks));
+    }
+
+    summary
+}
+
+fn normalize_bearing(bearing: f64) -> f64 {
+    (bearing % 360.0 + 360.0) % 360.0
+}
+
+/// Short uppercase identifier made up of the name and the runway designator (e.g. `PLANETAR29`).
+/// Identifiers that are already taken get a counter in front of the designator (e.g. `ENTENWE212`).
+fn waypoint_ident(name: &str, bearing: f64, taken: &mut HashSet<String>) -> String {
+    let designator = format!("{:02}", ((bearing / 10.0).round() as u32 + 35) % 36 + 1);
+    let name = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<String>();
+
+    let mut counter = 1;
+    loop {
+        let suffix = match counter {
+            1 => String::new(),
+            _ => counter.to_string(),
+        };
+        let length = WAYPOINT_IDENT_LENGTH - designator.len() - suffix.len();
+        let ident = name.chars().take(length).collect::<String>() + &suffix + &designator;
+
+        if taken.insert(ident.clone()) {
+            return ident;
+        }
+
+        counter += 1;
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_csv(text: &str) -> String {
+    if text.contains(|c| matches!(c, ',' | '"' | '\n')) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn waypoint_idents_are_unique() {
+        let locations = Parser::new()
+            .parse_locations(include_str!("../../../static/data/locations.yml"))
+            .ok()
+            .unwrap();
+        let aircraft = serde_yaml::from_str::<Vec<Aircraft>>(include_str!(
+            "../../../static/data/aircrafts.yml"
+        ))
+        .unwrap();
+
+        let csv = locations.export_csv(&aircraft[0]);
+        let idents = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(idents.contains(&"ENTENWER12"));
+        assert!(idents.contains(&"ENTENWE212"));
+        assert!(idents
+            .iter()
+            .all(|ident| ident.len() <= WAYPOINT_IDENT_LENGTH));
+        assert_eq!(idents.iter().collect::<HashSet<_>>().len(), idents.len());
+    }
+}
@@ -1,25 +1,59 @@
-use std::collections::hash_map::DefaultHasher;
+use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
 use std::ops::Add;
 
+use chrono::{DateTime, FixedOffset};
 use geo::geodesic_distance::GeodesicDistance;
 use geo::prelude::{Bearing, BoundingRect, Centroid, HaversineDestination};
 use geo::{point, Line, LineString, Point, Polygon, Rect};
 use js_sys::Array;
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
+use uom::si::{
+    f64::Length,
+    length::{foot, meter},
+};
 use wasm_bindgen::prelude::*;
 
-use crate::Aircraft;
+use crate::{
+    helpers::StableHasher, Aircraft, CropSeason, PresenceEvent, PresenceRule, Runway, SCREEN_HEIGHT,
+};
+
+/// Distance from the runway centerline (in meters) within which hazards affect the approach
+pub(crate) const HAZARD_CORRIDOR_WIDTH: f64 = 50.0;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct SerializedCoordinate([f64; 2]);
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-struct LocationCoordinates {
-    start: SerializedCoordinate,
-    end: SerializedCoordinate,
+#[serde(untagged)]
+enum LocationCoordinates {
+    /// Single landing line from start to end
+    Line {
+        start: SerializedCoordinate,
+        end: SerializedCoordinate,
+    },
+    /// Outline of a field that can be landed on in multiple directions
+    Area {
+        polygon: Vec<SerializedCoordinate>,
+        /// Direction (in degrees) in which one-way areas are landed on
+        #[serde(
+            rename = "landingBearing",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        landing_bearing: Option<f64>,
+    },
+}
+
+/// Recorded coordinates along with the landable lines found within them, which are too costly to search on every access
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(try_from = "LocationCoordinates", into = "LocationCoordinates")]
+struct Coordinates {
+    recorded: LocationCoordinates,
+    /// Reversible lines, restricted to one direction by the location if necessary.
+    /// Lines within areas point towards the landing bearing, if one has been recorded.
+    lines: Vec<Runway>,
 }
 
 #[wasm_bindgen]
@@ -57,6 +91,74 @@ pub enum HumanPresenceCategory {
     Unlikely = "Unlikely",
 }
 
+#[wasm_bindgen]
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "PascalCase")]
+pub enum SeaState {
+    /// Still water like lakes or ponds, no noteworthy current or waves
+    Calm = "Calm",
+    /// Slow-moving rivers or sheltered harbours with small waves
+    Moderate = "Moderate",
+    /// Strong or tidal currents and open water with significant swell
+    Rough = "Rough",
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "PascalCase")]
+pub enum RescueProximity {
+    /// Rescue services are stationed directly at the water (e.g. a lifeboat station)
+    OnSite = "OnSite",
+    /// Boats or rescue services can be expected to arrive within a couple of minutes
+    Nearby = "Nearby",
+    /// No rescue services close by, help will take a considerable amount of time to arrive
+    Remote = "Remote",
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DitchingCharacteristics {
+    /// Width of the body of water perpendicular to the ditching line in meters
+    pub width: f64,
+
+    /// Distance from the ditching line to the closest shore that can be reached by swimming in meters
+    pub shore_distance: f64,
+
+    /// Current and wave conditions that are usually encountered
+    pub sea_state: SeaState,
+
+    /// How quickly rescue services can be expected to arrive
+    pub rescue: RescueProximity,
+
+    /// Position of the closest rescue station, if any
+    rescue_station: Option<SerializedCoordinate>,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "PascalCase")]
+pub enum HazardType {
+    PowerLine = "PowerLine",
+    Trees = "Trees",
+    Tents = "Tents",
+    Buildings = "Buildings",
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Hazard {
+    /// Kind of obstacle
+    #[serde(rename = "type")]
+    pub kind: HazardType,
+
+    /// Position of the obstacle
+    position: SerializedCoordinate,
+
+    /// Height of the obstacle above the field elevation in feet
+    #[serde(rename = "height")]
+    raw_height: f64,
+}
+
 #[wasm_bindgen]
 #[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy, EnumIter)]
 #[serde(rename_all = "camelCase")]
@@ -80,6 +182,21 @@ impl Into<u8> for RiskClassification {
     }
 }
 
+impl RiskClassification {
+    /// Range of risk scores (between 0 and 100) that correspond to this classification
+    pub fn score_range(&self) -> (f64, f64) {
+        let index: u8 = (*self).into();
+        let width = 100.0 / 3.0;
+
+        (index as f64 * width, (index + 1) as f64 * width)
+    }
+
+    /// Classification whose score range contains the given score
+    pub fn from_score(score: f64) -> Self {
+        RiskClassification::from(((score * 3.0 / 100.0).floor() as u8).min(2))
+    }
+}
+
 impl From<u8> for RiskClassification {
     fn from(value: u8) -> Self {
         match value {
@@ -106,6 +223,11 @@ impl Add for RiskClassification {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Location {
+    /// Explicitly assigned identifier, falls back to a hash of the coordinates if absent
+    #[wasm_bindgen(skip)]
+    #[serde(rename = "id", default, skip_serializing_if = "Option::is_none")]
+    pub raw_id: Option<String>,
+
     #[wasm_bindgen(skip)]
     pub name: String,
 
@@ -133,51 +255,203 @@ pub struct Location {
     #[wasm_bindgen(readonly)]
     pub elevation: u32,
 
-    /// Start and end coordinates of the location
-    coordinates: LocationCoordinates,
+    /// Start and end coordinates or outline of the location
+    coordinates: Coordinates,
 
     /// Date at which the data was last verified
     #[wasm_bindgen(skip)]
+    #[serde(default)]
     pub survey_date: String,
 
     /// Human-readable remarks about the location
     #[wasm_bindgen(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub remarks: Option<String>,
-    //
-    // TODO Record other potential hazards like power lines or tents
-    // TODO Add free-form text and web links (e.g. event calendar)
+
+    /// Properties of the body of water relevant when ditching (only applicable to water surfaces)
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ditching: Option<DitchingCharacteristics>,
+
+    /// Obstacles on or around the landing surface which have to be cleared on approach
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hazards: Vec<Hazard>,
+
+    /// Recurring periods in which the human presence differs from the usual category
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub presence_schedule: Vec<PresenceRule>,
+
+    /// Known events taking place at the location
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<PresenceEvent>,
+
+    /// Whether the events are all that ever take place at the location, rather than just the known ones
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub events_exhaustive: bool,
+
+    /// Web link to the event calendar of the location
+    #[wasm_bindgen(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<String>,
+
+    /// Crops grown throughout the year (only applicable to agricultural locations)
+    #[wasm_bindgen(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crops: Vec<CropSeason>,
 }
 
 impl Location {
-    /// Beginning of the usable runway surface
-    pub fn start(&self) -> Point<f64> {
-        self.coordinates.start.point()
+    /// Unreviewed location consisting of a single landing line, with everything but the surface and usage left blank
+    pub fn draft_line(
+        name: String,
+        surface: SurfaceType,
+        usage: UsageType,
+        start: Point<f64>,
+        end: Point<f64>,
+    ) -> Self {
+        let coordinates = Coordinates::from_line(
+            SerializedCoordinate::from_point(start),
+            SerializedCoordinate::from_point(end),
+        );
+
+        Self::draft(name, surface, usage, coordinates)
+    }
+
+    /// Unreviewed location consisting of an area, with everything but the surface and usage left blank.
+    /// Fails if the outline has less than 3 points.
+    pub fn draft_area(
+        name: String,
+        surface: SurfaceType,
+        usage: UsageType,
+        polygon: Vec<Point<f64>>,
+    ) -> Result<Self, String> {
+        let coordinates = Coordinates::try_from(LocationCoordinates::Area {
+            polygon: polygon
+                .into_iter()
+                .map(SerializedCoordinate::from_point)
+                .collect(),
+            landing_bearing: None,
+        })?;
+
+        Ok(Self::draft(name, surface, usage, coordinates))
+    }
+
+    fn draft(
+        name: String,
+        surface: SurfaceType,
+        usage: UsageType,
+        coordinates: Coordinates,
+    ) -> Self {
+        Self {
+            raw_id: None,
+            name,
+            reversible: true,
+            surface,
+            human_presence: HumanPresenceCategory::default(),
+            usage,
+            elevation: 0,
+            coordinates,
+            survey_date: String::new(),
+            remarks: None,
+            ditching: None,
+            hazards: vec![],
+            presence_schedule: vec![],
+            events: vec![],
+            events_exhaustive: false,
+            calendar: None,
+            crops: vec![],
+        }
     }
 
-    /// End of the usable runway surface
-    pub fn end(&self) -> Point<f64> {
-        self.coordinates.end.point()
+    /// Straight lines that can be landed on at this location.
+    /// Areas yield the longest line for every direction sector, which one-way areas may only land on in the direction
+    /// closest to their landing bearing.
+    pub fn runways(&self) -> Vec<Runway> {
+        self.coordinates
+            .lines
+            .iter()
+            .map(|line| Runway::new(line.start, line.end, self.reversible))
+            .collect()
     }
 
-    /// Line representing the landable surface
-    pub fn line(&self) -> Line<f64> {
-        Line::new(self.start(), self.end())
+    /// Longest landable line of the location, `None` if an area does not contain any
+    pub fn primary_runway(&self) -> Option<Runway> {
+        self.runways()
+            .into_iter()
+            .max_by(|a, b| a.length().partial_cmp(&b.length()).unwrap())
     }
 
-    /// Center of the runway
+    /// Direction (in degrees) in which a one-way area is landed on, `None` for lines and areas without one
+    pub fn landing_bearing(&self) -> Option<f64> {
+        match &self.coordinates.recorded {
+            LocationCoordinates::Line { .. } => None,
+            LocationCoordinates::Area {
+                landing_bearing, ..
+            } => *landing_bearing,
+        }
+    }
+
+    /// Outline of the location if it has been recorded as an area
+    pub fn area(&self) -> Option<Polygon<f64>> {
+        match &self.coordinates.recorded {
+            LocationCoordinates::Line { .. } => None,
+            LocationCoordinates::Area { polygon, .. } => Some(Polygon::new(
+                polygon.iter().map(|c| c.point().0).collect(),
+                vec![],
+            )),
+        }
+    }
+
+    /// Coordinates as recorded, either the ends of the line or the vertices of the area
+    pub fn outline(&self) -> Vec<Point<f64>> {
+        match &self.coordinates.recorded {
+            LocationCoordinates::Line { start, end } => vec![start.point(), end.point()],
+            LocationCoordinates::Area { polygon, .. } => {
+                polygon.iter().map(|c| c.point()).collect()
+            }
+        }
+    }
+
+    /// Line representing the landable surface, `None` if an area does not contain any
+    pub fn line(&self) -> Option<Line<f64>> {
+        self.primary_runway()
+            .map(|runway| Line::new(runway.start, runway.end))
+    }
+
+    /// Center of the runway or area
     pub fn centroid(&self) -> Point<f64> {
-        self.line().centroid()
+        match &self.coordinates.recorded {
+            LocationCoordinates::Line { start, end } => {
+                Line::new(start.point(), end.point()).centroid()
+            }
+            LocationCoordinates::Area { polygon, .. } => self
+                .area()
+                .and_then(|area| area.centroid())
+                .unwrap_or_else(|| polygon[0].point()),
+        }
     }
 
     pub fn bounding_rect(&self) -> Rect<f64> {
-        self.line().bounding_rect()
+        match &self.coordinates.recorded {
+            LocationCoordinates::Line { start, end } => {
+                Line::new(start.point(), end.point()).bounding_rect()
+            }
+            LocationCoordinates::Area { polygon, .. } => self
+                .area()
+                .and_then(|area| area.bounding_rect())
+                .unwrap_or_else(|| Rect::new(polygon[0].point().0, polygon[0].point().0)),
+        }
     }
 
-    pub fn spaced_polygon(&self, distance: f64) -> Polygon<f64> {
-        let start = self.start();
-        let end = self.end();
-        let bearing = self.start().bearing(self.end());
-        let reverse_bearing = self.end().bearing(self.start());
+    /// Surroundings of the primary runway, `None` if an area does not contain any landable line
+    pub fn spaced_polygon(&self, distance: f64) -> Option<Polygon<f64>> {
+        let Runway { start, end, .. } = self.primary_runway()?;
+        let bearing = start.bearing(end);
+        let reverse_bearing = end.bearing(start);
 
         let a = start.haversine_destination(reverse_bearing + 45.0, distance);
         let b = start.haversine_destination(reverse_bearing - 45.0, distance);
@@ -185,60 +459,248 @@ impl Location {
         let c = end.haversine_destination(bearing + 45.0, distance);
         let d = end.haversine_destination(bearing - 45.0, distance);
 
-        Polygon::new(LineString::from(vec![a, b, c, d]), vec![])
+        Some(Polygon::new(LineString::from(vec![a, b, c, d]), vec![]))
     }
 
-    /// Returns a number of points with a given resolution in meters where a given airplane can land
-    pub fn landable_points(&self, aircraft: &Aircraft, resolution: f64) -> Vec<(Point<f64>, f64)> {
-        let inset_at_ends =
-            self.length() - aircraft.landing.total_distance_on_surface(&self.surface);
+    /// Points with a given resolution in meters along all cleared runways at which the aircraft may cross the
+    /// threshold and still come to a complete stop. Each point carries the landing bearing and its inset,
+    /// which is the distance from the beginning of the usable surface.
+    pub fn landable_points(
+        &self,
+        aircraft: &Aircraft,
+        resolution: f64,
+    ) -> Vec<(Point<f64>, f64, f64)> {
+        self.cleared_runways(aircraft)
+            .iter()
+            .flat_map(|runway| self.runway_landable_points(runway, aircraft, resolution))
+            .collect()
+    }
 
-        let mut points = vec![(self.start(), self.bearing())];
+    /// Runways of this location, shortened to the part that remains usable when the approach has to clear all hazards
+    pub fn cleared_runways(&self, aircraft: &Aircraft) -> Vec<Runway> {
+        self.runways()
+            .iter()
+            .flat_map(|runway| self.clear_runway(runway, aircraft))
+            .collect()
+    }
 
-        // Add the other start point if applicable
-        if self.reversible {
-            points.push((self.end(), self.reverse_bearing()));
+    /// Shortens a runway to the part that remains usable when the approach has to clear all hazards.
+    /// Reversible runways are split up into their individual directions if hazards affect them.
+    pub fn clear_runway(&self, runway: &Runway, aircraft: &Aircraft) -> Vec<Runway> {
+        let directions = runway.directions();
+        let segments = directions
+            .iter()
+            .map(|direction| self.cleared_segment(direction, aircraft))
+            .collect::<Vec<_>>();
+
+        let length = runway.length();
+        let unaffected = segments
+            .iter()
+            .all(|segment| *segment == Some((0.0, length)));
+
+        if unaffected {
+            return vec![*runway];
         }
 
-        // Iterate over the insets at each end
-        if inset_at_ends > 0.0 {
-            let bearing = self.bearing();
-            let step_count = (inset_at_ends / resolution).floor() as usize;
+        directions
+            .iter()
+            .zip(segments)
+            .filter_map(|(direction, segment)| {
+                let (start, end) = segment?;
+                let bearing = direction.bearing();
 
-            for i in 1..(step_count + 1) {
-                let step_distance = resolution * (i as f64);
+                Some(Runway::new(
+                    direction.start.haversine_destination(bearing, start),
+                    direction.start.haversine_destination(bearing, end),
+                    false,
+                ))
+            })
+            .collect()
+    }
 
-                // Inset from start
-                let inset_from_start = self.start().haversine_destination(bearing, step_distance);
-                points.push((inset_from_start, self.bearing()));
+    /// Finds the longest part of a (non-reversible) runway where the 50ft approach path clears all hazards.
+    /// Returns the distances from the threshold at which the usable surface starts and ends, if any.
+    ///
+    /// Obstacles in front of the touchdown zone displace the threshold while those further down the runway
+    /// cut it short, so every point at which the approach clears an obstacle is a candidate for the start.
+    fn cleared_segment(&self, runway: &Runway, aircraft: &Aircraft) -> Option<(f64, f64)> {
+        let length = runway.length();
+        let screen_height = Length::new::<foot>(SCREEN_HEIGHT).get::<meter>();
+        let descend_ratio = aircraft.landing.descend_ratio();
 
-                // Inset from end
-                if self.reversible {
-                    let inset_from_end = self
-                        .end()
-                        .haversine_destination(self.reverse_bearing(), step_distance);
-                    points.push((inset_from_end, self.reverse_bearing()));
+        // Along-track position and the point where the approach path has cleared it for every relevant obstacle
+        let obstacles = self
+            .hazards
+            .iter()
+            .filter_map(|hazard| {
+                let (along, across) = runway.relative_position(hazard.position());
+
+                if across.abs() > HAZARD_CORRIDOR_WIDTH || along >= length {
+                    return None;
                 }
+
+                let clearance = along + (hazard.height() - screen_height).max(0.0) * descend_ratio;
+                Some((along, clearance))
+            })
+            .collect::<Vec<_>>();
+
+        std::iter::once(0.0)
+            .chain(obstacles.iter().map(|(_, clearance)| *clearance))
+            .filter(|start| *start >= 0.0 && *start < length)
+            // Every obstacle before the start has to be cleared
+            .filter(|start| {
+                obstacles
+                    .iter()
+                    .all(|(along, clearance)| along >= start || clearance <= start)
+            })
+            // Obstacles after the start which are not cleared by the approach end the runway
+            .map(|start| {
+                let end = obstacles
+                    .iter()
+                    .filter(|(along, clearance)| *clearance > start && *along >= start)
+                    .map(|(along, _)| *along)
+                    .fold(length, f64::min);
+
+                (start, end)
+            })
+            .max_by(|(start_a, end_a), (start_b, end_b)| {
+                (end_a - start_a).partial_cmp(&(end_b - start_b)).unwrap()
+            })
+    }
+
+    /// Longest runway that remains once all hazards are cleared
+    fn best_cleared_runway(&self, aircraft: &Aircraft) -> Option<Runway> {
+        self.cleared_runways(aircraft)
+            .into_iter()
+            .max_by(|a, b| a.length().partial_cmp(&b.length()).unwrap())
+    }
+
+    /// Parsed survey date, `None` if it is missing or malformed
+    pub fn surveyed_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_str(&self.survey_date, "%Y-%m-%dT%H:%M:%S%z")
+            .or_else(|_| DateTime::parse_from_rfc3339(&self.survey_date))
+            .ok()
+    }
+
+    /// Crop standing on the location at the given point in time, if any
+    pub fn standing_crop_at(&self, time: &DateTime<FixedOffset>) -> Option<&CropSeason> {
+        self.crops.iter().find(|crop| crop.is_standing_at(time))
+    }
+
+    /// Human presence to be expected at the given point in time.
+    ///
+    /// Events take precedence over recurring rules, which in turn take precedence over the static category.
+    /// Overlapping events yield the densest presence among them.
+    /// Locations that only see humans during events are considered empty when their events are declared exhaustive.
+    pub fn human_presence_at(&self, time: &DateTime<FixedOffset>) -> HumanPresenceCategory {
+        let event = self
+            .events
+            .iter()
+            .filter(|e| e.takes_place_at(time))
+            .max_by_key(|e| e.presence.density());
+
+        if let Some(event) = event {
+            return event.presence;
+        }
+
+        if let Some(rule) = self.presence_schedule.iter().find(|r| r.applies_at(time)) {
+            return rule.presence;
+        }
+
+        match self.human_presence {
+            HumanPresenceCategory::EventOnly if self.events_exhaustive => {
+                HumanPresenceCategory::Unlikely
             }
+            presence => presence,
         }
+    }
+
+    /// Same as `inset` but for a specific runway of this location
+    pub fn runway_inset(&self, runway: &Runway, aircraft: &Aircraft) -> f64 {
+        let distance_required = aircraft.landing.total_distance_on_surface(&self.surface);
+        let distance_available = runway.length();
+
+        distance_available - distance_required
+    }
+
+    /// Same as `landable_points` but for a specific runway of this location.
+    /// The thresholds are always included, even if the runway is too short.
+    pub fn runway_landable_points(
+        &self,
+        runway: &Runway,
+        aircraft: &Aircraft,
+        resolution: f64,
+    ) -> Vec<(Point<f64>, f64, f64)> {
+        let inset = self.runway_inset(runway, aircraft).max(0.0);
+        let step_count = (inset / resolution).floor() as usize;
+
+        runway
+            .landing_directions()
+            .into_iter()
+            .flat_map(|(threshold, bearing)| {
+                (0..=step_count).map(move |step| {
+                    let distance = resolution * step as f64;
+                    (
+                        threshold.haversine_destination(bearing, distance),
+                        bearing,
+                        distance,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Same as `landing_headroom` but for a specific runway of this location
+    pub fn runway_landing_headroom(&self, runway: &Runway, aircraft: &Aircraft) -> f64 {
+        let required_landing_distance = aircraft.landing.total_distance_on_surface(&self.surface);
+        let remaining_landing_distance = runway.length() - required_landing_distance;
 
-        points
+        remaining_landing_distance / required_landing_distance
+    }
+
+    /// Recommended heading in degrees when ditching at this location.
+    /// Follows the surveyed line and, if possible, ends up close to the rescue station.
+    pub fn ditching_heading(&self) -> Option<f64> {
+        let ditching = self.ditching.as_ref()?;
+        let runway = self.primary_runway()?;
+
+        let heading = match &ditching.rescue_station {
+            Some(station) if runway.reversible => {
+                let station = station.point();
+                let start_distance = runway.start.geodesic_distance(&station);
+                let end_distance = runway.end.geodesic_distance(&station);
+
+                if start_distance < end_distance {
+                    runway.reverse_bearing()
+                } else {
+                    runway.bearing()
+                }
+            }
+            _ => runway.bearing(),
+        };
+
+        Some(heading)
     }
 }
 
 #[wasm_bindgen]
 impl Location {
+    /// Unique identifier of the location which remains stable across data revisions
     #[wasm_bindgen(getter)]
     pub fn id(&self) -> String {
-        let mut hasher = DefaultHasher::new();
+        self.raw_id.clone().unwrap_or_else(|| self.hash_id())
+    }
 
-        let start = self.coordinates.start.point();
-        hasher.write(&start.x().to_le_bytes());
-        hasher.write(&start.y().to_le_bytes());
+    /// Identifier derived from the coordinates of the location.
+    /// Deterministic but changes whenever the coordinates are corrected.
+    #[wasm_bindgen(getter, js_name = "hashID")]
+    pub fn hash_id(&self) -> String {
+        let mut hasher = StableHasher::default();
 
-        let end = self.coordinates.end.point();
-        hasher.write(&end.x().to_le_bytes());
-        hasher.write(&end.y().to_le_bytes());
+        for point in self.outline() {
+            hasher.write(&point.x().to_le_bytes());
+            hasher.write(&point.y().to_le_bytes());
+        }
 
         format!("{:x}", hasher.finish())
     }
@@ -254,22 +716,33 @@ impl Location {
         self.remarks.clone()
     }
 
+    /// Web link to the event calendar of the location
+    #[wasm_bindgen(getter)]
+    pub fn calendar(&self) -> Option<String> {
+        self.calendar.clone()
+    }
+
     /// Date at which the data was last verified
     #[wasm_bindgen(getter, js_name = "surveyDate")]
     pub fn survey_date(&self) -> String {
         self.survey_date.clone()
     }
 
-    /// Coordinates of the runway
+    /// Coordinates of the runway (or the longest line within an area), empty if an area does not contain any
     #[wasm_bindgen(getter)]
     pub fn coordinates(&self) -> Array {
+        let runway = match self.primary_runway() {
+            Some(runway) => runway,
+            None => return Array::new(),
+        };
+
         let start_array = Array::new();
-        start_array.push(&self.coordinates.start.longitude().into());
-        start_array.push(&self.coordinates.start.latitude().into());
+        start_array.push(&runway.start.x().into());
+        start_array.push(&runway.start.y().into());
 
         let end_array = Array::new();
-        end_array.push(&self.coordinates.end.longitude().into());
-        end_array.push(&self.coordinates.end.latitude().into());
+        end_array.push(&runway.end.x().into());
+        end_array.push(&runway.end.y().into());
 
         let coordinate_array = Array::new();
         coordinate_array.push(&start_array);
@@ -278,44 +751,43 @@ impl Location {
         coordinate_array
     }
 
-    /// Usable length of the runway
+    /// Usable length of the runway, zero if an area does not contain any landable line
     #[wasm_bindgen(getter)]
     pub fn length(&self) -> f64 {
-        self.start().geodesic_distance(&self.end())
+        self.primary_runway().map_or(0.0, |runway| runway.length())
     }
 
-    /// Bearing of runway in degrees (where 0º is North and 90º is East)
+    /// Bearing of runway in degrees (where 0º is North and 90º is East), NaN if an area does not contain any landable line
     #[wasm_bindgen(getter)]
     pub fn bearing(&self) -> f64 {
-        self.start().bearing(self.end())
+        self.primary_runway()
+            .map_or(f64::NAN, |runway| runway.bearing())
     }
 
-    /// Same as bearing but for the reverse direction
+    /// Same as bearing but for the reverse direction, NaN if the runway is not reversible
     #[wasm_bindgen(getter, js_name = "reverseBearing")]
     pub fn reverse_bearing(&self) -> f64 {
-        assert!(
-            self.reversible,
-            "Attempted to read reverse bearing of a non-reversible runway"
-        );
-        self.end().bearing(self.start())
+        self.primary_runway()
+            .filter(|runway| runway.reversible)
+            .map_or(f64::NAN, |runway| runway.reverse_bearing())
     }
 
     /// Maximum distance from the beginning of the landable surface where the 50ft height has to be reached
     /// in order to have sufficient landing run available to come to a complete stop.
     pub fn inset(&self, aircraft: &Aircraft) -> f64 {
-        let distance_required = aircraft.landing.total_distance_on_surface(&self.surface);
-        let distance_available = self.length();
-
-        distance_available - distance_required
+        match self.best_cleared_runway(aircraft) {
+            Some(runway) => self.runway_inset(&runway, aircraft),
+            None => -aircraft.landing.total_distance_on_surface(&self.surface),
+        }
     }
 
     /// Fraction of required landing distance that is available in addition to the base 100%
     #[wasm_bindgen(js_name = "landingHeadroom")]
     pub fn landing_headroom(&self, aircraft: &Aircraft) -> f64 {
-        let required_landing_distance = aircraft.landing.total_distance_on_surface(&self.surface);
-        let remaining_landing_distance = self.length() - required_landing_distance;
-
-        remaining_landing_distance / required_landing_distance
+        match self.best_cleared_runway(aircraft) {
+            Some(runway) => self.runway_landing_headroom(&runway, aircraft),
+            None => -1.0,
+        }
     }
 }
 
@@ -325,6 +797,79 @@ impl Default for HumanPresenceCategory {
     }
 }
 
+impl HumanPresenceCategory {
+    /// Rank of the category by the number of humans to be expected, from unlikely to dense
+    fn density(&self) -> u8 {
+        match self {
+            HumanPresenceCategory::Dense => 3,
+            HumanPresenceCategory::Sparse => 2,
+            HumanPresenceCategory::EventOnly => 1,
+            _ => 0,
+        }
+    }
+}
+
+impl Hazard {
+    /// Position of the obstacle
+    pub fn position(&self) -> Point<f64> {
+        self.position.point()
+    }
+
+    /// Height of the obstacle above the field elevation in meters
+    pub fn height(&self) -> f64 {
+        Length::new::<foot>(self.raw_height).get::<meter>()
+    }
+}
+
+impl Coordinates {
+    fn from_line(start: SerializedCoordinate, end: SerializedCoordinate) -> Self {
+        Self {
+            lines: vec![Runway::new(start.point(), end.point(), true)],
+            recorded: LocationCoordinates::Line { start, end },
+        }
+    }
+}
+
+impl TryFrom<LocationCoordinates> for Coordinates {
+    type Error = String;
+
+    fn try_from(recorded: LocationCoordinates) -> Result<Self, Self::Error> {
+        match recorded {
+            LocationCoordinates::Line { start, end } => Ok(Self::from_line(start, end)),
+            LocationCoordinates::Area { polygon, .. } if polygon.len() < 3 => {
+                Err(String::from("Area has less than 3 points"))
+            }
+            LocationCoordinates::Area {
+                polygon,
+                landing_bearing,
+            } => {
+                let points = polygon.iter().map(|c| c.point()).collect::<Vec<_>>();
+                let lines = Runway::longest_in_sectors(&points)
+                    .into_iter()
+                    .map(|line| match landing_bearing {
+                        Some(bearing) => line.towards(bearing),
+                        None => line,
+                    })
+                    .collect();
+
+                Ok(Self {
+                    lines,
+                    recorded: LocationCoordinates::Area {
+                        polygon,
+                        landing_bearing,
+                    },
+                })
+            }
+        }
+    }
+}
+
+impl From<Coordinates> for LocationCoordinates {
+    fn from(coordinates: Coordinates) -> Self {
+        coordinates.recorded
+    }
+}
+
 impl SerializedCoordinate {
     fn latitude(&self) -> f64 {
         self.0[0]
@@ -338,4 +883,333 @@ impl SerializedCoordinate {
         // For some reason it is "standard" to flip lat/lon to lon/lat
         point!(x: self.longitude(), y: self.latitude())
     }
+
+    fn from_point(point: Point<f64>) -> Self {
+        Self([point.y(), point.x()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AIRCRAFT: &str = "
+        id: C172
+        name: Cessna 172
+        mtow: 2450
+        takeoff: { groundRoll: 945, totalDistance: 1685, speed: 57 }
+        climb: { rate: 720, speed: 79 }
+        glide: { distance: 1.5, speed: 65 }
+        landing: { groundRoll: 550, totalDistance: 1295, speed: 62, descentRate: 1500 }
+    ";
+
+    /// Runway of roughly 600m pointing east with obstacles of the given height (in feet) at distances (in meters)
+    /// from its start
+    fn obstructed_location(reversible: bool, obstacles: &[(f64, f64)]) -> Location {
+        let mut location = serde_yaml::from_str::<Location>(&format!(
+            "
+            name: Feld
+            elevation: 30
+            reversible: {}
+            surface: Gras
+            usage: Agricultural
+            coordinates:
+              start: [53.5, 10.0]
+              end: [53.5, 10.009]
+            surveyDate: 2021-11-21T15:42:56+0000
+            ",
+            reversible
+        ))
+        .unwrap();
+
+        let runway = location.runways()[0];
+        location.hazards = obstacles
+            .iter()
+            .map(|(distance, height)| Hazard {
+                kind: HazardType::Trees,
+                position: SerializedCoordinate::from_point(
+                    runway
+                        .start
+                        .haversine_destination(runway.bearing(), *distance),
+                ),
+                raw_height: *height,
+            })
+            .collect();
+
+        location
+    }
+
+    /// Cleared part of every landing direction of the location
+    fn cleared_segments(location: &Location) -> Vec<Option<(f64, f64)>> {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+
+        location.runways()[0]
+            .directions()
+            .iter()
+            .map(|direction| location.cleared_segment(direction, &aircraft))
+            .collect()
+    }
+
+    /// Positions along the runway are measured on a sphere while its length is geodesic, which differ by a few meters
+    fn assert_segment(segment: Option<(f64, f64)>, expected: (f64, f64)) {
+        let (start, end) = segment.unwrap();
+
+        assert!(
+            (start - expected.0).abs() < 3.0 && (end - expected.1).abs() < 3.0,
+            "cleared {:?} instead of {:?}",
+            (start, end),
+            expected
+        );
+    }
+
+    #[test]
+    fn hash_id_is_stable() {
+        // Identifier assigned to this location in the dataset, which was derived using `DefaultHasher`
+        let location = serde_yaml::from_str::<Location>(
+            "
+            name: EDDH 05/23
+            elevation: 33
+            reversible: true
+            surface: Asphalt
+            usage: Aeronautical
+            coordinates:
+              start: [53.61997283331443, 9.967174743158031]
+              end: [53.636016866522446, 9.999672285710245]
+            surveyDate: 2021-11-21T15:42:56+0000
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(location.raw_id, None);
+        assert_eq!(location.hash_id(), "efa6461a221ca3ce");
+    }
+
+    #[test]
+    fn one_way_area_is_landed_towards_its_bearing() {
+        let area = |landing_bearing: &str| {
+            serde_yaml::from_str::<Location>(&format!(
+                "
+                name: Feld
+                elevation: 30
+                reversible: false
+                surface: Gras
+                usage: Agricultural
+                coordinates:
+                  polygon:
+                    - [53.5, 10.0]
+                    - [53.5, 10.01]
+                    - [53.503, 10.01]
+                    - [53.503, 10.0]
+                  {}
+                surveyDate: 2021-11-21T15:42:56+0000
+                ",
+                landing_bearing
+            ))
+            .unwrap()
+        };
+
+        let location = area("landingBearing: 250");
+        assert!(!location.runways().is_empty());
+        assert!(location.runways().iter().all(|runway| {
+            !runway.reversible && (runway.bearing() - 250.0).to_radians().cos() >= 0.0
+        }));
+        assert!((location.bearing() - 250.0).to_radians().cos() >= 0.0);
+        assert!(location.reverse_bearing().is_nan());
+
+        // Without a landing bearing the direction of the lines would be arbitrary
+        let errors = crate::Parser::location_errors(&[area("")]);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].1.contains("landing bearing"));
+    }
+
+    #[test]
+    fn obstacle_at_threshold_displaces_it() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let location = obstructed_location(true, &[(0.0, 100.0)]);
+        let length = location.runways()[0].length();
+
+        // Beyond the obstacle the approach descends from its height to the screen height
+        let screen_height = Length::new::<foot>(SCREEN_HEIGHT).get::<meter>();
+        let clearance = (Length::new::<foot>(100.0).get::<meter>() - screen_height)
+            * aircraft.landing.descend_ratio();
+
+        let segments = cleared_segments(&location);
+        assert_segment(segments[0], (clearance, length));
+        // Landing in the other direction the obstacle lies at the end of the runway
+        assert_segment(segments[1], (0.0, length));
+    }
+
+    #[test]
+    fn obstacle_mid_runway_cuts_it_short() {
+        let location = obstructed_location(true, &[(300.0, 100.0)]);
+        let length = location.runways()[0].length();
+
+        let segments = cleared_segments(&location);
+        assert_segment(segments[0], (0.0, 300.0));
+        assert_segment(segments[1], (0.0, length - 300.0));
+
+        // Obstacles below the screen height still end the runway, but do not affect the approach in front of it
+        let location = obstructed_location(true, &[(300.0, 40.0)]);
+        let segments = cleared_segments(&location);
+        assert_segment(segments[0], (0.0, 300.0));
+
+        let location = obstructed_location(true, &[(-100.0, 40.0)]);
+        let segments = cleared_segments(&location);
+        assert_segment(segments[0], (0.0, length));
+    }
+
+    #[test]
+    fn obstacle_blocks_approach_completely() {
+        let aircraft: Aircraft = serde_yaml::from_str(AIRCRAFT).unwrap();
+        let preferences: crate::Preferences = serde_json::from_str(
+            r#"{
+                "bank": 0.78,
+                "epsilon": 0.1,
+                "riskyLandingHeadroom": -0.05,
+                "unsafeLandingHeadroom": -0.15,
+                "eventLocationClassification": "risky",
+                "denselyCrowdedClassification": "unsafe"
+            }"#,
+        )
+        .unwrap();
+        let time = DateTime::parse_from_rfc3339("2022-06-01T12:00:00+02:00").unwrap();
+
+        // Clearing the obstacle takes longer than the runway, which may only be landed on in one direction
+        let location = obstructed_location(false, &[(0.0, 1000.0)]);
+        assert_segment(cleared_segments(&location)[0], (0.0, 0.0));
+
+        let assessment =
+            crate::Calculator::new().assess_risk_at(&preferences, &location, &aircraft, &time);
+        assert_eq!(assessment.obstacles, RiskClassification::Unsafe);
+        assert!(assessment
+            .reasons
+            .iter()
+            .any(|reason| reason.code == "OBSTACLES_BLOCK_APPROACH"));
+
+        // Obstacles on the runway only shorten it
+        let location = obstructed_location(true, &[(300.0, 100.0)]);
+        let assessment =
+            crate::Calculator::new().assess_risk_at(&preferences, &location, &aircraft, &time);
+        assert!(assessment
+            .reasons
+            .iter()
+            .any(|reason| reason.code == "OBSTACLES_SHORTEN_RUNWAY"));
+    }
+
+    /// Location which usually sees no humans, with the given presence schedule and events
+    fn scheduled_location(schedule: &str) -> Location {
+        serde_yaml::from_str(&format!(
+            "
+            name: Festwiese
+            elevation: 30
+            reversible: true
+            surface: Gras
+            usage: Event
+            humanPresence: EventOnly
+            coordinates:
+              start: [53.5, 10.0]
+              end: [53.5, 10.009]
+            surveyDate: 2021-11-21T15:42:56+0000
+            {}
+            ",
+            schedule
+        ))
+        .unwrap()
+    }
+
+    fn at(time: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(time).unwrap()
+    }
+
+    #[test]
+    fn events_take_precedence_over_schedule() {
+        use HumanPresenceCategory::*;
+
+        let location = scheduled_location(
+            "
+            presenceSchedule:
+              - { presence: Sparse, weekdays: [Sat, Sun], hours: [10, 18] }
+            events:
+              - { name: Flohmarkt, presence: Sparse, start: 2022-06-04T08:00:00+02:00, end: 2022-06-04T12:00:00+02:00 }
+              - { name: Stadtfest, start: 2022-06-04T11:00:00+02:00, end: 2022-06-05T23:00:00+02:00 }
+            ",
+        );
+
+        // Saturday morning before the schedule applies, only the market takes place
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-04T09:00:00+02:00")),
+            Sparse
+        );
+        // Both events overlap, the festival draws more people
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-04T11:30:00+02:00")),
+            Dense
+        );
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-05T23:00:00+02:00")),
+            Dense
+        );
+        // The following weekend only the schedule applies
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-11T12:00:00+02:00")),
+            Sparse
+        );
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-11T18:00:00+02:00")),
+            EventOnly
+        );
+        // Known events do not rule out others unless they are declared exhaustive
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-08T12:00:00+02:00")),
+            EventOnly
+        );
+    }
+
+    #[test]
+    fn exhaustive_events_leave_location_empty() {
+        use HumanPresenceCategory::*;
+
+        let location = scheduled_location(
+            "
+            eventsExhaustive: true
+            events:
+              - { name: Stadtfest, start: 2022-06-04T11:00:00+02:00, end: 2022-06-05T23:00:00+02:00 }
+            ",
+        );
+
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-04T11:00:00+02:00")),
+            Dense
+        );
+        assert_eq!(
+            location.human_presence_at(&at("2022-06-05T23:00:01+02:00")),
+            Unlikely
+        );
+        assert_eq!(
+            location.human_presence_at(&at("2021-06-05T12:00:00+02:00")),
+            Unlikely
+        );
+    }
+
+    #[test]
+    fn standing_crop_by_season() {
+        let location = scheduled_location(
+            "
+            crops:
+              - { crop: Winterweizen, season: { from: 09-25, until: 07-31 } }
+              - { crop: Zwischenfrucht, season: { from: 08-15, until: 09-10 } }
+            ",
+        );
+        let crop = |time| {
+            location
+                .standing_crop_at(&at(time))
+                .map(|crop| crop.crop.as_str())
+        };
+
+        assert_eq!(crop("2022-01-10T12:00:00+01:00"), Some("Winterweizen"));
+        assert_eq!(crop("2022-08-01T12:00:00+02:00"), None);
+        assert_eq!(crop("2022-08-15T12:00:00+02:00"), Some("Zwischenfrucht"));
+        assert_eq!(crop("2022-09-24T12:00:00+02:00"), None);
+        assert_eq!(crop("2022-09-25T12:00:00+02:00"), Some("Winterweizen"));
+    }
 }
@@ -1,7 +1,14 @@
 mod location;
 mod aircraft;
 mod parser;
+mod runway;
+mod schedule;
+mod export;
+mod wind;
 
 pub use location::*;
 pub use aircraft::*;
-pub use parser::*;
\ No newline at end of file
+pub use parser::*;
+pub use runway::*;
+pub use schedule::*;
+pub use wind::*;
\ No newline at end of file
@@ -1,12 +1,27 @@
 use crate::{Aircraft, Location};
-use geo::{prelude::HaversineDistance, Point};
+use geo::{prelude::HaversineDistance, Coordinate, Point, Rect};
 use js_sys::{Array, Map};
+use rstar::{RTree, RTreeObject, AABB};
 use serde_json::json;
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::HashMap};
 use wasm_bindgen::prelude::*;
 
+/// Mean radius of the earth (in meters) as used by the haversine calculations
+const EARTH_RADIUS: f64 = 6_371_008.8;
+/// Radius (in meters) with which the search for the nearest locations starts out
+const NEAREST_SEARCH_RADIUS: f64 = 10_000.0;
+
 #[wasm_bindgen]
-pub struct LocationMap(HashMap<String, Location>);
+pub struct LocationMap {
+    locations: HashMap<String, Location>,
+    index: RTree<IndexedLocation>,
+}
+
+/// Entry in the spatial index of a `LocationMap`, spanning the bounding box of a location in degrees
+struct IndexedLocation {
+    id: String,
+    envelope: AABB<[f64; 2]>,
+}
 
 #[wasm_bindgen]
 pub struct Parser {}
@@ -36,45 +51,463 @@ impl Parser {
     #[wasm_bindgen(js_name = parseLocations)]
     pub fn parse_locations(&mut self, yaml: &str) -> Result<LocationMap, JsValue> {
         let locations = serde_yaml::from_str::<Vec<Location>>(yaml).map_err(|e| e.to_string())?;
-        let map = locations
-            .into_iter()
-            .map(|location| (location.id(), location))
-            .collect();
 
-        Ok(LocationMap(map))
+        if let Some((_, error)) = Self::location_errors(&locations).into_iter().next() {
+            return Err(error.into());
+        }
+
+        Ok(LocationMap::new(locations))
+    }
+}
+
+impl Parser {
+    /// Problems that make the given locations unusable, each with the index of the offending location
+    pub fn location_errors(locations: &[Location]) -> Vec<(usize, String)> {
+        let mut errors = Vec::new();
+        let mut names_by_id = HashMap::new();
+        let url_safe = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+
+        for (index, location) in locations.iter().enumerate() {
+            if location.runways().is_empty() {
+                errors.push((
+                    index,
+                    format!("Location '{}' has no landable line", location.name),
+                ));
+            }
+
+            // Lines within areas are searched regardless of their direction
+            if !location.reversible
+                && location.area().is_some()
+                && location.landing_bearing().is_none()
+            {
+                errors.push((
+                    index,
+                    format!("One-way area '{}' has no landing bearing", location.name),
+                ));
+            }
+
+            if location.survey_date.is_empty() {
+                errors.push((
+                    index,
+                    format!("Location '{}' has no survey date", location.name),
+                ));
+            }
+
+            let id = location.id();
+
+            if id.is_empty() || !id.chars().all(url_safe) {
+                errors.push((
+                    index,
+                    format!("Location '{}' has an invalid ID: '{}'", location.name, id),
+                ));
+            }
+
+            if let Some(other) = names_by_id.insert(id.clone(), &location.name) {
+                errors.push((
+                    index,
+                    format!(
+                        "Locations '{}' and '{}' share the same ID: '{}'",
+                        other, location.name, id
+                    ),
+                ));
+            }
+        }
+
+        errors
     }
 }
 
 #[wasm_bindgen]
 impl LocationMap {
     pub fn keys(&self) -> Array {
-        self.0.keys().into_iter().map(JsValue::from).collect()
+        self.locations
+            .keys()
+            .into_iter()
+            .map(JsValue::from)
+            .collect()
     }
 
     pub fn get(&self, id: String) -> Option<Location> {
-        self.0.get(&id).cloned()
+        self.locations.get(&id).cloned()
     }
 
     pub fn closest(&self, latitude: f64, longitude: f64) -> JsValue {
         let point = Point::new(longitude, latitude);
 
-        self.0
-            .values()
-            .min_by_key(|location| location.centroid().haversine_distance(&point) as usize)
-            .map(|location| {
+        self.nearest(point, 1)
+            .first()
+            .map(|(location, distance)| {
                 let json = json!({
                     "location": location.id(),
-                    "distance": location.centroid().haversine_distance(&point)
+                    "distance": distance
                 });
 
                 JsValue::from(&json.to_string())
             })
             .unwrap_or(JsValue::NULL)
     }
+
+    /// IDs of the `count` locations closest to the given point, ordered by distance
+    #[wasm_bindgen(js_name = "nearestIDs")]
+    pub fn nearest_ids(&self, latitude: f64, longitude: f64, count: usize) -> Array {
+        self.nearest(Point::new(longitude, latitude), count)
+            .into_iter()
+            .map(|(location, _)| JsValue::from(location.id()))
+            .collect()
+    }
+
+    /// IDs of all locations that extend to within `radius` meters of the given point
+    #[wasm_bindgen(js_name = "idsWithinRadius")]
+    pub fn ids_within_radius(&self, latitude: f64, longitude: f64, radius: f64) -> Array {
+        self.within_radius(Point::new(longitude, latitude), radius)
+            .into_iter()
+            .map(|location| JsValue::from(location.id()))
+            .collect()
+    }
+
+    /// IDs of all locations that overlap the given bounding box
+    #[wasm_bindgen(js_name = "idsWithinBounds")]
+    pub fn ids_within_bounds(&self, west: f64, south: f64, east: f64, north: f64) -> Array {
+        let bounds = Rect::new(
+            Coordinate { x: west, y: south },
+            Coordinate { x: east, y: north },
+        );
+
+        self.within_bounds(&bounds)
+            .into_iter()
+            .map(|location| JsValue::from(location.id()))
+            .collect()
+    }
 }
 
 impl LocationMap {
+    pub fn new(locations: Vec<Location>) -> Self {
+        let index = RTree::bulk_load(
+            locations
+                .iter()
+                .map(|location| {
+                    let rect = location.bounding_rect();
+
+                    IndexedLocation {
+                        id: location.id(),
+                        envelope: AABB::from_corners(
+                            [rect.min().x, rect.min().y],
+                            [rect.max().x, rect.max().y],
+                        ),
+                    }
+                })
+                .collect(),
+        );
+
+        let locations = locations
+            .into_iter()
+            .map(|location| (location.id(), location))
+            .collect();
+
+        Self { locations, index }
+    }
+
     pub fn locations(&self) -> impl Iterator<Item = &Location> {
-        self.0.values().into_iter()
+        self.locations.values().into_iter()
+    }
+
+    /// Locations whose bounding box overlaps the given one
+    pub fn within_bounds(&self, bounds: &Rect<f64>) -> Vec<&Location> {
+        let envelope = AABB::from_corners(
+            [bounds.min().x, bounds.min().y],
+            [bounds.max().x, bounds.max().y],
+        );
+
+        self.index
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|entry| &self.locations[&entry.id])
+            .collect()
+    }
+
+    /// Locations that extend to within `radius` meters of the given point.
+    /// Distances are measured to the closest point on the bounding box of each location.
+    pub fn within_radius(&self, point: Point<f64>, radius: f64) -> Vec<&Location> {
+        let bounds = expand_bounds(&Rect::new(point.0, point.0), radius);
+        let envelope = AABB::from_corners(
+            [bounds.min().x, bounds.min().y],
+            [bounds.max().x, bounds.max().y],
+        );
+
+        self.index
+            .locate_in_envelope_intersecting(&envelope)
+            .filter(|entry| {
+                let [x, y] = entry.envelope.min_point(&[point.x(), point.y()]);
+                Point::new(x, y).haversine_distance(&point) <= radius
+            })
+            .map(|entry| &self.locations[&entry.id])
+            .collect()
+    }
+
+    /// Up to `count` locations ordered by the distance (in meters) between their centroid and the given point
+    pub fn nearest(&self, point: Point<f64>, count: usize) -> Vec<(&Location, f64)> {
+        let mut radius = NEAREST_SEARCH_RADIUS;
+
+        loop {
+            let mut candidates = self
+                .within_radius(point, radius)
+                .into_iter()
+                .map(|location| (location, location.centroid().haversine_distance(&point)))
+                .collect::<Vec<_>>();
+
+            candidates.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+            // Centroids are never closer than the bounding box, thus any location outside the radius
+            // is further away than the candidates as long as those are within the radius themselves.
+            let complete =
+                count == 0 || (candidates.len() >= count && candidates[count - 1].1 <= radius);
+            let exhausted = radius >= std::f64::consts::PI * EARTH_RADIUS;
+
+            if complete || exhausted {
+                candidates.truncate(count);
+                return candidates;
+            }
+
+            radius *= 2.0;
+        }
+    }
+}
+
+impl RTreeObject for IndexedLocation {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+
+/// Grows a geographic bounding box in every direction so that it covers all points within `distance` meters.
+/// Boxes that would wrap around the poles or the antimeridian span all longitudes instead.
+pub fn expand_bounds(bounds: &Rect<f64>, distance: f64) -> Rect<f64> {
+    let angular_distance = (distance / EARTH_RADIUS).to_degrees();
+    let south = bounds.min().y - angular_distance;
+    let north = bounds.max().y + angular_distance;
+
+    let (west, east) = if south <= -90.0 || north >= 90.0 {
+        (-180.0, 180.0)
+    } else {
+        // The widest longitudinal extent of a circle is reached at the latitude closest to a pole
+        let latitude = bounds.min().y.abs().max(bounds.max().y.abs()).to_radians();
+        let extent = ((distance / EARTH_RADIUS).sin() / latitude.cos())
+            .min(1.0)
+            .asin()
+            .to_degrees();
+        let (west, east) = (bounds.min().x - extent, bounds.max().x + extent);
+
+        if west < -180.0 || east > 180.0 {
+            (-180.0, 180.0)
+        } else {
+            (west, east)
+        }
+    };
+
+    Rect::new(
+        Coordinate {
+            x: west,
+            y: south.max(-90.0),
+        },
+        Coordinate {
+            x: east,
+            y: north.min(90.0),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::prelude::HaversineDestination;
+
+    fn dataset() -> LocationMap {
+        Parser::new()
+            .parse_locations(include_str!("../../../static/data/locations.yml"))
+            .unwrap()
+    }
+
+    /// Short north-south line starting at the given coordinates
+    fn line_location(latitude: f64, longitude: f64) -> Location {
+        serde_yaml::from_str(&format!(
+            "
+            name: Wiese
+            elevation: 0
+            reversible: true
+            surface: Gras
+            usage: Agricultural
+            humanPresence: Unlikely
+            coordinates:
+              start: [{}, {}]
+              end: [{}, {}]
+            surveyDate: 2021-11-21T15:42:56+0000
+            ",
+            latitude,
+            longitude,
+            latitude + 0.001,
+            longitude
+        ))
+        .unwrap()
+    }
+
+    /// Sorted IDs of the given locations
+    fn ids(locations: Vec<&Location>) -> Vec<String> {
+        let mut ids = locations
+            .into_iter()
+            .map(|location| location.id())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    /// Distance (in meters) between the point and the closest point on the bounding box of the location
+    fn bounds_distance(location: &Location, point: Point<f64>) -> f64 {
+        let rect = location.bounding_rect();
+        let closest = Point::new(
+            point.x().max(rect.min().x).min(rect.max().x),
+            point.y().max(rect.min().y).min(rect.max().y),
+        );
+
+        closest.haversine_distance(&point)
+    }
+
+    #[test]
+    fn within_radius_matches_brute_force() {
+        let map = dataset();
+        let points = [
+            Point::new(10.0, 53.6),
+            Point::new(9.5, 53.2),
+            Point::new(-120.0, 40.0),
+        ];
+
+        for point in points.iter() {
+            for radius in [0.0, 1_000.0, 20_000.0, 200_000.0].iter() {
+                let expected = map
+                    .locations()
+                    .filter(|location| bounds_distance(location, *point) <= *radius)
+                    .collect();
+
+                assert_eq!(
+                    ids(map.within_radius(*point, *radius)),
+                    ids(expected),
+                    "{:?} within {} m",
+                    point,
+                    radius
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let map = dataset();
+        let mut distances = map
+            .locations()
+            .map(|location| {
+                location
+                    .centroid()
+                    .haversine_distance(&Point::new(10.0, 53.6))
+            })
+            .collect::<Vec<_>>();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        for count in [0, 1, 5, 50, map.locations().count() + 1].iter() {
+            let nearest = map
+                .nearest(Point::new(10.0, 53.6), *count)
+                .into_iter()
+                .map(|(_, distance)| distance)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                nearest,
+                distances.iter().take(*count).cloned().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_doubles_search_radius() {
+        // Neither location is within the initial search radius of the point
+        let map = LocationMap::new(vec![line_location(53.0, 10.0), line_location(-33.9, 151.2)]);
+        let point = Point::new(10.0, 50.0);
+
+        let nearest = map.nearest(point, 2);
+
+        assert_eq!(nearest.len(), 2);
+        assert!(nearest[0].1 > NEAREST_SEARCH_RADIUS);
+        assert_eq!(nearest[0].0.id(), line_location(53.0, 10.0).id());
+        assert_eq!(nearest[1].0.id(), line_location(-33.9, 151.2).id());
+        assert!(nearest[0].1 < nearest[1].1);
+    }
+
+    #[test]
+    fn nearest_on_empty_map() {
+        let map = LocationMap::new(Vec::new());
+
+        assert!(map.nearest(Point::new(10.0, 53.6), 3).is_empty());
+        assert!(map
+            .within_radius(Point::new(10.0, 53.6), 1_000.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn expanded_bounds_cover_circle() {
+        for (longitude, latitude) in [(10.0, 53.6), (-70.0, -60.0), (0.0, 0.0)].iter() {
+            let point = Point::new(*longitude, *latitude);
+
+            for distance in [100.0, 50_000.0, 1_000_000.0].iter() {
+                let bounds = expand_bounds(&Rect::new(point.0, point.0), *distance);
+
+                for bearing in (0..360).step_by(5) {
+                    let destination = point.haversine_destination(bearing as f64, *distance);
+
+                    assert!(
+                        destination.x() >= bounds.min().x - 1e-9
+                            && destination.x() <= bounds.max().x + 1e-9
+                            && destination.y() >= bounds.min().y - 1e-9
+                            && destination.y() <= bounds.max().y + 1e-9,
+                        "{:?} not within {:?}",
+                        destination,
+                        bounds
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn expanded_bounds_at_antimeridian() {
+        let point = Coordinate { x: 179.95, y: 0.0 };
+        let bounds = expand_bounds(&Rect::new(point, point), 20_000.0);
+
+        assert_eq!((bounds.min().x, bounds.max().x), (-180.0, 180.0));
+        assert!(bounds.min().y > -1.0 && bounds.max().y < 1.0);
+
+        // About 11 km away on the other side of the antimeridian
+        let map = LocationMap::new(vec![line_location(0.0, -179.95)]);
+
+        assert_eq!(map.within_radius(Point(point), 20_000.0).len(), 1);
+        assert_eq!(map.nearest(Point(point), 1).len(), 1);
+    }
+
+    #[test]
+    fn expanded_bounds_at_poles() {
+        for latitude in [89.9, -89.9].iter() {
+            let point = Coordinate {
+                x: 0.0,
+                y: *latitude,
+            };
+            let bounds = expand_bounds(&Rect::new(point, point), 50_000.0);
+
+            assert_eq!((bounds.min().x, bounds.max().x), (-180.0, 180.0));
+            assert!(bounds.min().y >= -90.0 && bounds.max().y <= 90.0);
+            assert!(bounds.min().y == -90.0 || bounds.max().y == 90.0);
+        }
+
+        // About 22 km away on the other side of the north pole
+        let map = LocationMap::new(vec![line_location(89.9, 180.0)]);
+
+        assert_eq!(map.within_radius(Point::new(0.0, 89.9), 30_000.0).len(), 1);
     }
 }
@@ -0,0 +1,285 @@
+use crate::dubin::{geometric_point_to_geographic, relative_geometric_position_of};
+use geo::geodesic_distance::GeodesicDistance;
+use geo::prelude::{Bearing, EuclideanDistance};
+use geo::Point;
+
+/// Width of the direction sectors in which an area is searched for landing lines (in degrees)
+const SECTOR_SIZE: usize = 10;
+/// Angular step at which lines are tested within each sector (in degrees)
+const SECTOR_RESOLUTION: usize = 1;
+/// Lines shorter than this (in meters) are considered degenerate
+const MINIMUM_RUNWAY_LENGTH: f64 = 1.0;
+
+/// Straight line within a location that can be landed on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Runway {
+    /// Beginning of the usable surface
+    pub start: Point<f64>,
+    /// End of the usable surface
+    pub end: Point<f64>,
+    /// Whether the runway may be used in both directions
+    pub reversible: bool,
+}
+
+impl Runway {
+    pub fn new(start: Point<f64>, end: Point<f64>, reversible: bool) -> Self {
+        Self {
+            start,
+            end,
+            reversible,
+        }
+    }
+
+    /// Usable length of the runway
+    pub fn length(&self) -> f64 {
+        self.start.geodesic_distance(&self.end)
+    }
+
+    /// Bearing of runway in degrees (where 0º is North and 90º is East)
+    pub fn bearing(&self) -> f64 {
+        self.start.bearing(self.end)
+    }
+
+    /// Same as bearing but for the reverse direction
+    pub fn reverse_bearing(&self) -> f64 {
+        self.end.bearing(self.start)
+    }
+
+    /// Position of a point as the distance along the centerline from the start
+    /// and the lateral offset from the centerline (both in meters)
+    pub fn relative_position(&self, point: Point<f64>) -> (f64, f64) {
+        if point == self.start {
+            return (0.0, 0.0);
+        }
+
+        // Geometric coordinates have the x-axis pointing east and the y-axis pointing south
+        let bearing = self.bearing().to_radians();
+        let position = relative_geometric_position_of(point, self.start);
+        let along = position.x() * bearing.sin() - position.y() * bearing.cos();
+        let across = position.x() * bearing.cos() + position.y() * bearing.sin();
+
+        (along, across)
+    }
+
+    /// Thresholds and the bearing with which they can be landed on
+    pub fn landing_directions(&self) -> Vec<(Point<f64>, f64)> {
+        let mut directions = vec![(self.start, self.bearing())];
+
+        if self.reversible {
+            directions.push((self.end, self.reverse_bearing()));
+        }
+
+        directions
+    }
+
+    /// Same runway pointing in the direction which is closest to the given bearing (in degrees)
+    pub fn towards(&self, bearing: f64) -> Runway {
+        if (self.bearing() - bearing).to_radians().cos() < 0.0 {
+            Runway::new(self.end, self.start, self.reversible)
+        } else {
+            *self
+        }
+    }
+
+    /// Splits the runway into one non-reversible runway per landing direction
+    pub fn directions(&self) -> Vec<Runway> {
+        let mut directions = vec![Runway::new(self.start, self.end, false)];
+
+        if self.reversible {
+            directions.push(Runway::new(self.end, self.start, false));
+        }
+
+        directions
+    }
+
+    /// Finds the longest straight line inside the given area for each direction sector.
+    /// Only sectors between 0º and 180º are searched and every line is returned as reversible,
+    /// one-way areas point the lines towards their landing bearing (see `Location::runways`).
+    pub fn longest_in_sectors(area: &[Point<f64>]) -> Vec<Runway> {
+        if area.len() < 3 {
+            return vec![];
+        }
+
+        // Work in a local geometric coordinate system centered on the area
+        let count = area.len() as f64;
+        let origin = Point::new(
+            area.iter().map(|p| p.x()).sum::<f64>() / count,
+            area.iter().map(|p| p.y()).sum::<f64>() / count,
+        );
+        let vertices = area
+            .iter()
+            .map(|p| relative_geometric_position_of(*p, origin))
+            .collect::<Vec<_>>();
+
+        let mut sectors: Vec<Option<(Point<f64>, Point<f64>, f64)>> = vec![None; 180 / SECTOR_SIZE];
+
+        for bearing in (0..180).step_by(SECTOR_RESOLUTION) {
+            let sector = &mut sectors[bearing / SECTOR_SIZE];
+
+            if let Some((start, end)) = longest_chord(&vertices, (bearing as f64).to_radians()) {
+                let length = start.euclidean_distance(&end);
+
+                if length >= MINIMUM_RUNWAY_LENGTH
+                    && sector.map(|(_, _, l)| length > l).unwrap_or(true)
+                {
+                    *sector = Some((start, end, length));
+                }
+            }
+        }
+
+        sectors
+            .into_iter()
+            .flatten()
+            .map(|(start, end, _)| {
+                Runway::new(
+                    geometric_point_to_geographic(start, origin),
+                    geometric_point_to_geographic(end, origin),
+                    true,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Longest segment with the given bearing (in radians) that lies completely within the polygon.
+///
+/// The length of the segments inside the polygon changes linearly between the offsets of two vertices,
+/// thus it is sufficient to look at lines passing (right next to) every vertex.
+fn longest_chord(vertices: &[Point<f64>], bearing: f64) -> Option<(Point<f64>, Point<f64>)> {
+    // Geometric coordinates have the x-axis pointing east and the y-axis pointing south
+    let direction = Point::new(bearing.sin(), -bearing.cos());
+    let normal = Point::new(bearing.cos(), bearing.sin());
+
+    let along = |p: &Point<f64>| p.x() * direction.x() + p.y() * direction.y();
+    let across = |p: &Point<f64>| p.x() * normal.x() + p.y() * normal.y();
+
+    let mut longest: Option<(f64, f64, f64)> = None;
+
+    for vertex in vertices {
+        for offset in [across(vertex) - 1e-6, across(vertex) + 1e-6] {
+            // Intersect the line at the given offset with every edge of the polygon
+            let mut intersections = vertices
+                .iter()
+                .zip(vertices.iter().cycle().skip(1))
+                .filter_map(|(a, b)| {
+                    let (offset_a, offset_b) = (across(a), across(b));
+
+                    if (offset_a - offset) * (offset_b - offset) < 0.0 {
+                        let fraction = (offset - offset_a) / (offset_b - offset_a);
+                        Some(along(a) + fraction * (along(b) - along(a)))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            intersections.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            // Every pair of intersections encloses a segment that is inside the polygon
+            for segment in intersections.chunks_exact(2) {
+                let length = segment[1] - segment[0];
+
+                if longest.map(|(_, _, l)| length > l).unwrap_or(true) {
+                    longest = Some((offset, segment[0], length));
+                }
+            }
+        }
+    }
+
+    longest.map(|(offset, start, length)| {
+        let point_at = |distance: f64| {
+            Point::new(
+                offset * normal.x() + distance * direction.x(),
+                offset * normal.y() + distance * direction.y(),
+            )
+        };
+
+        (point_at(start), point_at(start + length))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polygon from geometric coordinates in meters, with the x-axis pointing east and the y-axis pointing south
+    fn polygon(vertices: &[(f64, f64)]) -> Vec<Point<f64>> {
+        vertices.iter().map(|(x, y)| Point::new(*x, *y)).collect()
+    }
+
+    /// Length of a chord and whether all of it lies within the polygon
+    fn chord_length(vertices: &[Point<f64>], bearing: f64) -> f64 {
+        let (start, end) = longest_chord(vertices, bearing.to_radians()).unwrap();
+        let area = geo::Polygon::new(vertices.to_vec().into(), vec![]);
+
+        for step in 1..100 {
+            let point = start + (end - start) * (step as f64 / 100.0);
+            assert!(
+                geo::prelude::Contains::contains(&area, &point),
+                "chord at {}º leaves the area at {:?}",
+                bearing,
+                point
+            );
+        }
+
+        start.euclidean_distance(&end)
+    }
+
+    #[test]
+    fn longest_chord_of_convex_polygon() {
+        let rectangle = polygon(&[(0.0, 0.0), (100.0, 0.0), (100.0, 40.0), (0.0, 40.0)]);
+
+        assert!((chord_length(&rectangle, 90.0) - 100.0).abs() < 1e-3);
+        assert!((chord_length(&rectangle, 0.0) - 40.0).abs() < 1e-3);
+        // The diagonal runs from corner to corner
+        let diagonal = 40.0_f64.atan2(100.0).to_degrees();
+        assert!((chord_length(&rectangle, 90.0 - diagonal) - 107.703).abs() < 1e-2);
+    }
+
+    #[test]
+    fn longest_chord_of_concave_polygon() {
+        // L-shape made of two 100m by 20m bars meeting in the north-west
+        let l_shape = polygon(&[
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 20.0),
+            (20.0, 20.0),
+            (20.0, 100.0),
+            (0.0, 100.0),
+        ]);
+
+        assert!((chord_length(&l_shape, 90.0) - 100.0).abs() < 1e-3);
+        assert!((chord_length(&l_shape, 180.0) - 100.0).abs() < 1e-3);
+        // Across the notch the chord is limited to the corner where both bars meet
+        assert!((chord_length(&l_shape, 45.0) - 40.0 * 2.0_f64.sqrt()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn longest_lines_in_sectors() {
+        let origin = Point::new(10.0, 53.5);
+        let area = polygon(&[(0.0, 0.0), (100.0, 0.0), (100.0, 40.0), (0.0, 40.0)])
+            .into_iter()
+            .map(|point| geometric_point_to_geographic(point, origin))
+            .collect::<Vec<_>>();
+
+        let runways = Runway::longest_in_sectors(&area);
+        let longest = runways.iter().map(Runway::length).fold(0.0, f64::max);
+
+        assert_eq!(runways.len(), 180 / SECTOR_SIZE);
+        assert!(runways.iter().all(|runway| runway.reversible));
+        assert!(runways.iter().all(|runway| runway.length() < 107.8));
+        assert!((longest - 107.7).abs() < 1.0, "{}", longest);
+        assert!(Runway::longest_in_sectors(&area[..2]).is_empty());
+    }
+
+    #[test]
+    fn runway_towards_bearing() {
+        let runway = Runway::new(Point::new(10.0, 53.5), Point::new(10.01, 53.5), true);
+
+        assert_eq!(runway.towards(100.0), runway);
+        assert_eq!(
+            runway.towards(300.0),
+            Runway::new(runway.end, runway.start, true)
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::HumanPresenceCategory;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Day within a year independent of the actual year, serialized as `MM-DD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DayOfYear {
+    month: u32,
+    day: u32,
+}
+
+/// Recurring range of days within each year (inclusive on both ends).
+/// Wraps around the end of the year when `from` comes after `until`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Season {
+    pub from: DayOfYear,
+    pub until: DayOfYear,
+}
+
+/// Recurring period in which a different human presence is to be expected
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceRule {
+    /// Presence to be expected while the rule applies
+    pub presence: HumanPresenceCategory,
+
+    /// Days of the week on which the rule applies, every day if empty
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+
+    /// Part of the year in which the rule applies, all year if absent
+    #[serde(default)]
+    pub season: Option<Season>,
+
+    /// Local hours of the day `[from, until)` during which the rule applies, all day if absent
+    #[serde(default)]
+    pub hours: Option<[u32; 2]>,
+}
+
+/// One-off event taking place at a location
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresenceEvent {
+    pub name: String,
+
+    /// Presence to be expected during the event
+    #[serde(default = "PresenceEvent::default_presence")]
+    pub presence: HumanPresenceCategory,
+
+    /// Beginning of the event (RFC 3339)
+    pub start: DateTime<FixedOffset>,
+
+    /// End of the event (RFC 3339)
+    pub end: DateTime<FixedOffset>,
+}
+
+/// Crop grown on a field, rendering it unlandable from sowing until it has been harvested
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CropSeason {
+    /// Name of the crop (e.g. corn or rapeseed)
+    pub crop: String,
+
+    /// Part of the year from sowing to harvest during which the crop is standing
+    pub season: Season,
+}
+
+impl DayOfYear {
+    pub fn of<D: Datelike>(date: &D) -> Self {
+        Self {
+            month: date.month(),
+            day: date.day(),
+        }
+    }
+}
+
+impl TryFrom<String> for DayOfYear {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        // Parse it within a leap year so that the 29th of February is accepted
+        let date = NaiveDate::parse_from_str(&format!("2000-{}", value), "%Y-%m-%d")
+            .map_err(|_| format!("Not a valid day of the year (MM-DD): {}", value))?;
+
+        Ok(Self::of(&date))
+    }
+}
+
+impl From<DayOfYear> for String {
+    fn from(day: DayOfYear) -> Self {
+        format!("{:02}-{:02}", day.month, day.day)
+    }
+}
+
+impl Season {
+    pub fn contains<D: Datelike>(&self, date: &D) -> bool {
+        let day = DayOfYear::of(date);
+
+        if self.from <= self.until {
+            self.from <= day && day <= self.until
+        } else {
+            self.from <= day || day <= self.until
+        }
+    }
+}
+
+impl PresenceRule {
+    pub fn applies_at(&self, time: &DateTime<FixedOffset>) -> bool {
+        let weekday = self.weekdays.is_empty() || self.weekdays.contains(&time.weekday());
+        let season = self.season.map(|s| s.contains(time)).unwrap_or(true);
+        let hours = self
+            .hours
+            .map(|[from, until]| from <= time.hour() && time.hour() < until)
+            .unwrap_or(true);
+
+        weekday && season && hours
+    }
+}
+
+impl PresenceEvent {
+    fn default_presence() -> HumanPresenceCategory {
+        HumanPresenceCategory::Dense
+    }
+
+    pub fn takes_place_at(&self, time: &DateTime<FixedOffset>) -> bool {
+        self.start <= *time && *time <= self.end
+    }
+}
+
+impl CropSeason {
+    pub fn is_standing_at<D: Datelike>(&self, date: &D) -> bool {
+        self.season.contains(date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn season(from: &str, until: &str) -> Season {
+        Season {
+            from: DayOfYear::try_from(from.to_string()).unwrap(),
+            until: DayOfYear::try_from(until.to_string()).unwrap(),
+        }
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    fn at(time: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(time).unwrap()
+    }
+
+    #[test]
+    fn season_boundaries() {
+        let summer = season("06-01", "08-31");
+
+        assert!(!summer.contains(&date("2022-05-31")));
+        assert!(summer.contains(&date("2022-06-01")));
+        assert!(summer.contains(&date("2022-08-31")));
+        assert!(!summer.contains(&date("2022-09-01")));
+    }
+
+    #[test]
+    fn season_wrapping_year_end() {
+        let winter = season("11-15", "02-29");
+
+        assert!(!winter.contains(&date("2022-11-14")));
+        assert!(winter.contains(&date("2022-11-15")));
+        assert!(winter.contains(&date("2022-12-31")));
+        assert!(winter.contains(&date("2023-01-01")));
+        assert!(winter.contains(&date("2023-02-28")));
+        assert!(winter.contains(&date("2024-02-29")));
+        assert!(!winter.contains(&date("2024-03-01")));
+        assert!(!winter.contains(&date("2023-07-01")));
+    }
+
+    #[test]
+    fn day_of_year_parsing() {
+        assert_eq!(String::from(season("02-29", "12-01").from), "02-29");
+        assert!(DayOfYear::try_from(String::from("13-01")).is_err());
+        assert!(DayOfYear::try_from(String::from("June")).is_err());
+    }
+
+    #[test]
+    fn presence_rule_windows() {
+        let rule: PresenceRule = serde_yaml::from_str(
+            "{ presence: Dense, weekdays: [Sat, Sun], season: { from: 12-01, until: 01-31 }, hours: [10, 16] }",
+        )
+        .unwrap();
+
+        // Saturday the 31st of December and Sunday the 1st of January, hours include the start but not the end
+        assert!(!rule.applies_at(&at("2022-12-31T09:59:59+01:00")));
+        assert!(rule.applies_at(&at("2022-12-31T10:00:00+01:00")));
+        assert!(rule.applies_at(&at("2023-01-01T15:59:59+01:00")));
+        assert!(!rule.applies_at(&at("2023-01-01T16:00:00+01:00")));
+        // Weekdays and the rest of the year are not affected
+        assert!(!rule.applies_at(&at("2022-12-30T12:00:00+01:00")));
+        assert!(!rule.applies_at(&at("2023-02-04T12:00:00+01:00")));
+
+        let always: PresenceRule = serde_yaml::from_str("{ presence: Sparse }").unwrap();
+        assert!(always.applies_at(&at("2022-07-13T03:00:00+02:00")));
+    }
+
+    #[test]
+    fn event_boundaries() {
+        let event: PresenceEvent = serde_yaml::from_str(
+            "{ name: Stadtfest, start: 2022-06-04T11:00:00+02:00, end: 2022-06-05T23:00:00+02:00 }",
+        )
+        .unwrap();
+
+        assert_eq!(event.presence, HumanPresenceCategory::Dense);
+        assert!(!event.takes_place_at(&at("2022-06-04T10:59:59+02:00")));
+        assert!(event.takes_place_at(&at("2022-06-04T11:00:00+02:00")));
+        // Points in time are compared regardless of their offset
+        assert!(event.takes_place_at(&at("2022-06-05T21:00:00+00:00")));
+        assert!(!event.takes_place_at(&at("2022-06-05T21:00:01+00:00")));
+    }
+
+    #[test]
+    fn crop_season_boundaries() {
+        let corn: CropSeason =
+            serde_yaml::from_str("{ crop: Mais, season: { from: 04-20, until: 10-15 } }").unwrap();
+
+        // Standing from the first minute of sowing until the last minute of the harvest day, in local time
+        assert!(!corn.is_standing_at(&at("2022-04-19T23:59:59+02:00")));
+        assert!(corn.is_standing_at(&at("2022-04-20T00:00:00+02:00")));
+        assert!(corn.is_standing_at(&at("2022-10-15T23:59:59+02:00")));
+        assert!(!corn.is_standing_at(&at("2022-10-16T00:00:00+02:00")));
+        assert!(!corn.is_standing_at(&at("2022-01-15T12:00:00+01:00")));
+    }
+
+    #[test]
+    fn crop_season_wrapping_year_end() {
+        let wheat: CropSeason =
+            serde_yaml::from_str("{ crop: Winterweizen, season: { from: 09-25, until: 07-31 } }")
+                .unwrap();
+
+        assert!(!wheat.is_standing_at(&date("2022-09-24")));
+        assert!(wheat.is_standing_at(&date("2022-09-25")));
+        assert!(wheat.is_standing_at(&date("2022-12-31")));
+        assert!(wheat.is_standing_at(&date("2023-01-01")));
+        assert!(wheat.is_standing_at(&date("2023-07-31")));
+        assert!(!wheat.is_standing_at(&date("2023-08-01")));
+        assert!(!wheat.is_standing_at(&date("2023-09-01")));
+    }
+}
@@ -0,0 +1,184 @@
+use geo::{prelude::EuclideanDistance, Point};
+use serde::{Deserialize, Serialize};
+use uom::si::{
+    f64::Velocity,
+    velocity::{knot, meter_per_second},
+};
+
+/// Number of altitudes at which a wind profile is sampled when averaging it over a layer
+const LAYER_SAMPLES: usize = 32;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Wind {
+    /// Direction (in degrees) from which the wind is blowing
+    pub direction: f64,
+    /// Speed of the wind (in knots)
+    pub speed: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct WindLayer {
+    /// Altitude (in meters) at which the wind has been observed
+    pub altitude: f64,
+    #[serde(flatten)]
+    pub wind: Wind,
+}
+
+/// Wind passed in from JS, either the same at all altitudes or observed at a number of altitudes
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum WindConditions {
+    Uniform(Wind),
+    Profile(Vec<WindLayer>),
+}
+
+impl Wind {
+    /// Velocity (in meters per second) of the air mass with the x-axis pointing east and the y-axis pointing north
+    pub fn velocity(&self) -> Point<f64> {
+        let speed = Velocity::new::<knot>(self.speed).get::<meter_per_second>();
+        let bearing = (self.direction + 180.0).to_radians();
+
+        Point::new(speed * bearing.sin(), speed * bearing.cos())
+    }
+}
+
+impl WindConditions {
+    /// Velocity of the air mass at the given altitude. Between two layers the velocities are interpolated,
+    /// below the lowest and above the highest layer the wind is assumed to stay the same.
+    pub fn velocity_at(&self, altitude: f64) -> Point<f64> {
+        let layers = match self {
+            WindConditions::Uniform(wind) => return wind.velocity(),
+            WindConditions::Profile(layers) => layers,
+        };
+
+        let below = layers
+            .iter()
+            .filter(|layer| layer.altitude <= altitude)
+            .max_by(|a, b| a.altitude.partial_cmp(&b.altitude).unwrap());
+        let above = layers
+            .iter()
+            .filter(|layer| layer.altitude > altitude)
+            .min_by(|a, b| a.altitude.partial_cmp(&b.altitude).unwrap());
+
+        match (below, above) {
+            (Some(below), Some(above)) => {
+                let fraction = (altitude - below.altitude) / (above.altitude - below.altitude);
+                below.wind.velocity() + (above.wind.velocity() - below.wind.velocity()) * fraction
+            }
+            (Some(layer), None) | (None, Some(layer)) => layer.wind.velocity(),
+            (None, None) => Point::new(0.0, 0.0),
+        }
+    }
+
+    /// Highest wind speed (in meters per second) at any altitude
+    pub fn maximum_speed(&self) -> f64 {
+        let origin = Point::new(0.0, 0.0);
+
+        match self {
+            WindConditions::Uniform(wind) => wind.velocity().euclidean_distance(&origin),
+            WindConditions::Profile(layers) => layers
+                .iter()
+                .map(|layer| layer.wind.velocity().euclidean_distance(&origin))
+                .fold(0.0, f64::max),
+        }
+    }
+
+    /// Mean velocity of the air mass experienced while descending at a constant rate from the top
+    /// to the bottom altitude, which equals the average over the layer in between
+    pub fn mean_velocity(&self, bottom: f64, top: f64) -> Point<f64> {
+        let thickness = top - bottom;
+
+        (0..LAYER_SAMPLES)
+            .map(|i| bottom + thickness * (i as f64 + 0.5) / LAYER_SAMPLES as f64)
+            .map(|altitude| self.velocity_at(altitude))
+            .fold(Point::new(0.0, 0.0), |sum, velocity| sum + velocity)
+            / LAYER_SAMPLES as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(altitude: f64, direction: f64, speed: f64) -> WindLayer {
+        WindLayer {
+            altitude,
+            wind: Wind { direction, speed },
+        }
+    }
+
+    fn assert_velocity(velocity: Point<f64>, expected: Point<f64>) {
+        assert!(
+            velocity.euclidean_distance(&expected) < 1e-9,
+            "{:?} instead of {:?}",
+            velocity,
+            expected
+        );
+    }
+
+    #[test]
+    fn calm_wind_does_not_move_air() {
+        let calm = WindConditions::Profile(vec![layer(0.0, 270.0, 0.0), layer(1000.0, 90.0, 0.0)]);
+
+        for conditions in [
+            calm,
+            WindConditions::Uniform(Wind {
+                direction: 180.0,
+                speed: 0.0,
+            }),
+            WindConditions::Profile(vec![]),
+        ]
+        .iter()
+        {
+            assert_velocity(conditions.velocity_at(500.0), Point::new(0.0, 0.0));
+            assert_velocity(conditions.mean_velocity(0.0, 1000.0), Point::new(0.0, 0.0));
+            assert_eq!(conditions.maximum_speed(), 0.0);
+        }
+    }
+
+    #[test]
+    fn constant_wind_at_all_altitudes() {
+        // Wind from the west moves the air east at about 10 m/s
+        let wind = Wind {
+            direction: 270.0,
+            speed: 19.438_444_924_406_05,
+        };
+        let expected = Point::new(10.0, 0.0);
+
+        assert_velocity(wind.velocity(), expected);
+
+        let uniform = WindConditions::Uniform(wind);
+        let profile = WindConditions::Profile(vec![
+            layer(300.0, 270.0, wind.speed),
+            layer(1500.0, 270.0, wind.speed),
+        ]);
+
+        for conditions in [uniform, profile].iter() {
+            for altitude in [0.0, 300.0, 1000.0, 3000.0].iter() {
+                assert_velocity(conditions.velocity_at(*altitude), expected);
+            }
+
+            assert_velocity(conditions.mean_velocity(0.0, 2000.0), expected);
+            assert!((conditions.maximum_speed() - 10.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn layers_are_interpolated() {
+        let profile =
+            WindConditions::Profile(vec![layer(1000.0, 180.0, 10.0), layer(0.0, 180.0, 0.0)]);
+        let northward = |speed: f64| Velocity::new::<knot>(speed).get::<meter_per_second>();
+
+        assert_velocity(profile.velocity_at(-100.0), Point::new(0.0, 0.0));
+        assert_velocity(profile.velocity_at(250.0), Point::new(0.0, northward(2.5)));
+        assert_velocity(
+            profile.velocity_at(2000.0),
+            Point::new(0.0, northward(10.0)),
+        );
+        // The average over a layer in which the wind grows linearly is the wind halfway up
+        assert_velocity(
+            profile.mean_velocity(0.0, 1000.0),
+            Point::new(0.0, northward(5.0)),
+        );
+    }
+}
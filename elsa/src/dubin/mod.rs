@@ -14,6 +14,11 @@ pub use self::geo::*;
 pub use structs::{Arc, Circle, Direction, DubinPath, Tangent};
 
 use self::ccc::ccc_paths;
+use ::geo::prelude::EuclideanDistance;
+use std::f64::consts::FRAC_PI_2;
+
+/// Offset (in radians) from the start angles at which paths jump in length, large enough to land on either side
+const CRITICAL_ANGLE_OFFSET: f64 = 1e-7;
 
 pub fn calculate_dubin_path_candidates(
     start: Point,
@@ -38,6 +43,60 @@ pub fn calculate_dubin_path_candidates(
     calculate_paths(&start, &end, radius)
 }
 
+/// Start angles at which the extremes of the shortest path length as a function of the start angle are usually found.
+/// These are the angles pointing directly away from an end circle or straight along a tangent onto it, and those at
+/// which a start circle is exactly 2r (inner tangents) or 4r (CCC paths) away from an end circle. Paths jump in length
+/// at the latter, so they are returned slightly offset to either side.
+pub fn critical_start_angles(
+    start: Point,
+    end: Point,
+    end_angle: Angle,
+    radius: Distance,
+) -> Vec<Angle> {
+    let end_angle = end_angle.get::<radian>();
+    let radius = radius.get::<meter>();
+    let mut angles = Vec::new();
+
+    for side in [-FRAC_PI_2, FRAC_PI_2] {
+        let center = end
+            + Point::new(
+                radius * (end_angle + side).cos(),
+                radius * (end_angle + side).sin(),
+            );
+        let distance = start.euclidean_distance(&center);
+        let direction = (start.y() - center.y()).atan2(start.x() - center.x());
+
+        // Heading directly away from the circle
+        angles.push(direction);
+
+        // Heading straight for the point at which a tangent from the start touches the circle
+        if distance > radius {
+            let spread = (radius / distance).acos();
+
+            for touch in [direction - spread, direction + spread] {
+                let point = center + Point::new(radius * touch.cos(), radius * touch.sin());
+                angles.push((point.y() - start.y()).atan2(point.x() - start.x()));
+            }
+        }
+
+        // Inner tangents connect circles turning in opposite directions while CCC paths connect those turning
+        // in the same one. A start circle lies to the left of the start angle if it is turning left.
+        for (factor, turn) in [(2.0f64, side), (4.0, -side)] {
+            let cos = ((factor.powi(2) - 1.0) * radius.powi(2) - distance.powi(2))
+                / (2.0 * radius * distance);
+
+            if cos.abs() <= 1.0 {
+                for offset in [cos.acos(), -cos.acos()] {
+                    let angle = direction + offset + turn;
+                    angles.extend([angle - CRITICAL_ANGLE_OFFSET, angle + CRITICAL_ANGLE_OFFSET]);
+                }
+            }
+        }
+    }
+
+    angles.into_iter().map(Angle::new::<radian>).collect()
+}
+
 fn calculate_paths(start: &DirectedPoint, end: &DirectedPoint, radius: f64) -> Vec<DubinPath> {
     // Build the four circles
     let circles = CircleSet::new(&start, &end, radius);
@@ -1,3 +1,5 @@
+use std::hash::Hasher;
+
 /// Finds the number dividing a real number space into two parts using binary search up to a precision of epsilon.
 /// Yields undefined behavior when the search space does not consist of exactly two areas.
 /// The test function is expected to return true if the tested number is below the bound.
@@ -18,3 +20,123 @@ where
 
     return (high + low) / 2.0;
 }
+
+/// Finds the root of a function between a point where it is negative (or zero) and one where it is positive using
+/// the Illinois variant of regula falsi, which converges much faster than a binary search on smooth functions.
+/// Expects the values of the function at both points and returns the last point found not to be positive
+/// once the search space has shrunk to less than epsilon.
+pub fn regula_falsi<F>(
+    (mut low, mut low_value): (f64, f64),
+    (mut high, mut high_value): (f64, f64),
+    epsilon: f64,
+    function: F,
+) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    // Whether the low end has been moved in the previous iteration
+    let mut moved_low = None;
+
+    while high - low >= epsilon {
+        // Nudging the secant towards the end that has not been moved lets that end catch up once the secant is
+        // within epsilon of the root, which the search space would otherwise only approach from one side
+        let nudge = match moved_low {
+            Some(true) => epsilon / 3.0,
+            Some(false) => -epsilon / 3.0,
+            None => 0.0,
+        };
+        let secant = low - low_value * (high - low) / (high_value - low_value) + nudge;
+        // Fall back to bisection where the secant is unusable
+        let middle = if secant.is_finite() {
+            secant.max(low + epsilon / 3.0).min(high - epsilon / 3.0)
+        } else {
+            (high + low) / 2.0
+        };
+        let value = function(middle);
+
+        if value <= 0.0 {
+            low = middle;
+            low_value = value;
+
+            // Halving the value at an end that stays put pulls the secant towards it, so both ends keep moving
+            if moved_low == Some(true) {
+                high_value /= 2.0;
+            }
+            moved_low = Some(true);
+        } else {
+            high = middle;
+            high_value = value;
+
+            if moved_low == Some(false) {
+                low_value /= 2.0;
+            }
+            moved_low = Some(false);
+        }
+    }
+
+    low
+}
+
+/// SipHash-1-3 with zero keys which is what `DefaultHasher::new()` used at the time of writing.
+/// Unlike the standard library hasher, its output is guaranteed to stay the same across Rust versions.
+#[derive(Default)]
+pub struct StableHasher {
+    bytes: Vec<u8>,
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let mut v = [
+            0x736f6d6570736575u64,
+            0x646f72616e646f6du64,
+            0x6c7967656e657261u64,
+            0x7465646279746573u64,
+        ];
+
+        let round = |v: &mut [u64; 4]| {
+            v[0] = v[0].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(13) ^ v[0];
+            v[0] = v[0].rotate_left(32);
+            v[2] = v[2].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(16) ^ v[2];
+            v[0] = v[0].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(21) ^ v[0];
+            v[2] = v[2].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(17) ^ v[2];
+            v[2] = v[2].rotate_left(32);
+        };
+
+        let chunks = self.bytes.chunks_exact(8);
+        let remainder = chunks.remainder();
+
+        for chunk in chunks {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(chunk);
+            let m = u64::from_le_bytes(word);
+
+            v[3] ^= m;
+            round(&mut v);
+            v[0] ^= m;
+        }
+
+        // The last block contains the remaining bytes and the total length in its most significant byte
+        let mut last = [0u8; 8];
+        last[..remainder.len()].copy_from_slice(remainder);
+        let b = u64::from_le_bytes(last) | ((self.bytes.len() as u64) << 56);
+
+        v[3] ^= b;
+        round(&mut v);
+        v[0] ^= b;
+
+        v[2] ^= 0xff;
+        for _ in 0..3 {
+            round(&mut v);
+        }
+
+        v[0] ^ v[1] ^ v[2] ^ v[3]
+    }
+}
@@ -0,0 +1,190 @@
+use super::{ForeignFeature, ForeignGeometry, Import};
+use ::geojson::{Feature, GeoJson, Value};
+use geo::Point;
+use std::collections::HashMap;
+
+/// Creates location drafts from the LineString and Polygon features of a GeoJSON document
+pub fn import_geojson(source: &str) -> Result<Import, String> {
+    let features = match source.parse::<GeoJson>().map_err(|e| e.to_string())? {
+        GeoJson::FeatureCollection(collection) => collection.features,
+        GeoJson::Feature(feature) => vec![feature],
+        GeoJson::Geometry(_) => return Err(String::from("Expected features, found a geometry")),
+    };
+
+    let mut import = Import::default();
+
+    for (index, feature) in features.into_iter().enumerate() {
+        import.add(foreign_feature(index, feature));
+    }
+
+    Ok(import)
+}
+
+fn foreign_feature(index: usize, feature: Feature) -> ForeignFeature {
+    let tags = feature
+        .properties
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(value) => (key, value),
+            value => (key, value.to_string()),
+        })
+        .collect::<HashMap<_, _>>();
+
+    let name = tags.get("name").cloned();
+    let label = match &name {
+        Some(name) => format!("feature #{} ({})", index, name),
+        None => format!("feature #{}", index),
+    };
+
+    // Positions consist of at least the longitude and latitude
+    let points = |positions: &[Vec<f64>]| {
+        positions
+            .iter()
+            .map(|position| match position.as_slice() {
+                [x, y, ..] => Some(Point::new(*x, *y)),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+    let invalid = || ForeignGeometry::Invalid(String::from("Position has less than 2 values"));
+    let mut notes = Vec::new();
+
+    let geometry = match feature.geometry.map(|g| g.value) {
+        Some(Value::LineString(line)) => points(&line).map_or_else(invalid, ForeignGeometry::Line),
+        Some(Value::Polygon(rings)) if !rings.is_empty() => {
+            if rings.len() > 1 {
+                notes.push(format!(
+                    "Ignored {} holes within the polygon",
+                    rings.len() - 1
+                ));
+            }
+
+            points(&rings[0]).map_or_else(invalid, ForeignGeometry::Area)
+        }
+        Some(Value::Polygon(_)) => ForeignGeometry::Unsupported(String::from("empty Polygon")),
+        Some(Value::Point(_)) => ForeignGeometry::Unsupported(String::from("Point")),
+        Some(Value::MultiPoint(_)) => ForeignGeometry::Unsupported(String::from("MultiPoint")),
+        Some(Value::MultiLineString(_)) => {
+            ForeignGeometry::Unsupported(String::from("MultiLineString"))
+        }
+        Some(Value::MultiPolygon(_)) => ForeignGeometry::Unsupported(String::from("MultiPolygon")),
+        Some(Value::GeometryCollection(_)) => {
+            ForeignGeometry::Unsupported(String::from("GeometryCollection"))
+        }
+        None => ForeignGeometry::Unsupported(String::from("no geometry")),
+    };
+
+    ForeignFeature {
+        label,
+        name,
+        tags,
+        geometry,
+        notes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UsageType;
+
+    #[test]
+    fn features_are_mapped() {
+        let import = import_geojson(
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "type": "Feature",
+                        "properties": { "name": "Piste", "aeroway": "runway", "surface": "asphalt" },
+                        "geometry": { "type": "LineString", "coordinates": [[10.0, 53.5, 12.0], [10.009, 53.5, 12.0]] }
+                    },
+                    {
+                        "type": "Feature",
+                        "properties": { "landuse": "meadow" },
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [
+                                [[10.0, 53.6], [10.005, 53.6], [10.005, 53.605], [10.0, 53.605], [10.0, 53.6]],
+                                [[10.001, 53.601], [10.002, 53.601], [10.002, 53.602], [10.001, 53.601]]
+                            ]
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(import.drafts.len(), 2);
+        assert_eq!(import.drafts[0].name, "Piste");
+        assert_eq!(import.drafts[0].usage, UsageType::Aeronautical);
+        assert_eq!(
+            import.drafts[0].outline(),
+            vec![Point::new(10.0, 53.5), Point::new(10.009, 53.5)]
+        );
+        assert_eq!(import.drafts[1].name, "feature #1");
+        assert_eq!(import.drafts[1].outline().len(), 4);
+        assert_eq!(import.notes.len(), 1);
+        assert_eq!(import.notes[0].feature, "feature #1");
+        assert_eq!(
+            import.notes[0].message,
+            "Ignored 1 holes within the polygon"
+        );
+    }
+
+    #[test]
+    fn unusable_features_are_skipped() {
+        let import = import_geojson(
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "type": "Feature",
+                        "properties": { "name": "Baum", "landuse": "meadow" },
+                        "geometry": { "type": "Point", "coordinates": [10.0, 53.5] }
+                    },
+                    {
+                        "type": "Feature",
+                        "properties": { "landuse": "meadow" },
+                        "geometry": { "type": "LineString", "coordinates": [[10.0, 53.5], [10.009]] }
+                    },
+                    {
+                        "type": "Feature",
+                        "properties": { "landuse": "meadow" },
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [[[10.0, 53.6], [10.000005, 53.6], [10.000005, 53.600005], [10.0, 53.6]]]
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let notes = import
+            .notes
+            .iter()
+            .map(|note| (note.feature.as_str(), note.skipped, note.message.as_str()))
+            .collect::<Vec<_>>();
+
+        assert!(import.drafts.is_empty());
+        assert_eq!(
+            notes,
+            vec![
+                ("feature #0 (Baum)", true, "Unsupported geometry: Point"),
+                ("feature #1", true, "Position has less than 2 values"),
+                (
+                    "feature #2",
+                    true,
+                    "Area does not contain any landable line"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn geometries_are_rejected() {
+        assert!(import_geojson(r#"{ "type": "Point", "coordinates": [10.0, 53.5] }"#).is_err());
+    }
+}
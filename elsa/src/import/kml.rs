@@ -0,0 +1,223 @@
+use super::{ForeignFeature, ForeignGeometry, Import};
+use geo::Point;
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+
+/// Creates location drafts from the LineString and Polygon placemarks of a KML document.
+/// Extended data of each placemark is treated like a set of tags.
+pub fn import_kml(source: &str) -> Result<Import, String> {
+    let document = Document::parse(source).map_err(|e| e.to_string())?;
+    let mut import = Import::default();
+
+    for (index, placemark) in document
+        .descendants()
+        .filter(|node| is_element(node, "Placemark"))
+        .enumerate()
+    {
+        import.add(foreign_feature(index, placemark));
+    }
+
+    Ok(import)
+}
+
+/// Compares the tag name while ignoring the namespace, which differs between KML versions
+fn is_element(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is_element(child, name))
+}
+
+fn text(node: Option<Node>) -> Option<String> {
+    node.and_then(|node| node.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+/// Parses a whitespace separated list of `longitude,latitude[,altitude]` tuples
+fn coordinates(node: Option<Node>) -> Vec<Point<f64>> {
+    text(node)
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|tuple| {
+            let mut values = tuple.split(',').map(|value| value.parse::<f64>());
+
+            match (values.next(), values.next()) {
+                (Some(Ok(longitude)), Some(Ok(latitude))) => Some(Point::new(longitude, latitude)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn foreign_feature(index: usize, placemark: Node) -> ForeignFeature {
+    let name = text(child(placemark, "name"));
+    let label = match &name {
+        Some(name) => format!("placemark #{} ({})", index, name),
+        None => format!("placemark #{}", index),
+    };
+
+    // Both untyped <Data name=""><value/></Data> and schema based <SimpleData name=""/> are supported
+    let mut tags = HashMap::new();
+    for data in placemark.descendants() {
+        let value = if is_element(&data, "Data") {
+            text(child(data, "value"))
+        } else if is_element(&data, "SimpleData") {
+            text(Some(data))
+        } else {
+            continue;
+        };
+
+        if let (Some(key), Some(value)) = (data.attribute("name"), value) {
+            tags.insert(key.to_string(), value);
+        }
+    }
+
+    let mut notes = Vec::new();
+    let geometry = placemark.descendants().find_map(|node| {
+        if is_element(&node, "LineString") {
+            Some(ForeignGeometry::Line(coordinates(child(
+                node,
+                "coordinates",
+            ))))
+        } else if is_element(&node, "Polygon") {
+            let holes = node
+                .children()
+                .filter(|child| is_element(child, "innerBoundaryIs"))
+                .count();
+
+            if holes > 0 {
+                notes.push(format!("Ignored {} holes within the polygon", holes));
+            }
+
+            let ring = child(node, "outerBoundaryIs").and_then(|outer| child(outer, "LinearRing"));
+            Some(ForeignGeometry::Area(coordinates(
+                ring.and_then(|ring| child(ring, "coordinates")),
+            )))
+        } else if is_element(&node, "MultiGeometry") || is_element(&node, "Point") {
+            Some(ForeignGeometry::Unsupported(
+                node.tag_name().name().to_string(),
+            ))
+        } else {
+            None
+        }
+    });
+
+    ForeignFeature {
+        label,
+        name,
+        tags,
+        geometry: geometry
+            .unwrap_or_else(|| ForeignGeometry::Unsupported(String::from("no geometry"))),
+        notes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UsageType;
+
+    #[test]
+    fn placemarks_are_mapped() {
+        let import = import_kml(
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+            <kml xmlns="http://www.opengis.net/kml/2.2">
+              <Document>
+                <Placemark>
+                  <name>Wiese</name>
+                  <ExtendedData>
+                    <Data name="landuse"><value>meadow</value></Data>
+                  </ExtendedData>
+                  <LineString>
+                    <coordinates>10.0,53.5,0 10.004,53.5,0 10.009,53.5,0</coordinates>
+                  </LineString>
+                </Placemark>
+                <Placemark>
+                  <ExtendedData>
+                    <SchemaData schemaUrl="#tags">
+                      <SimpleData name="leisure">park</SimpleData>
+                    </SchemaData>
+                  </ExtendedData>
+                  <Polygon>
+                    <outerBoundaryIs>
+                      <LinearRing>
+                        <coordinates>
+                          10.0,53.6 10.005,53.6 10.005,53.605 10.0,53.605 10.0,53.6
+                        </coordinates>
+                      </LinearRing>
+                    </outerBoundaryIs>
+                  </Polygon>
+                </Placemark>
+              </Document>
+            </kml>"##,
+        )
+        .unwrap();
+
+        assert_eq!(import.drafts.len(), 2);
+        assert_eq!(import.drafts[0].name, "Wiese");
+        assert_eq!(import.drafts[0].usage, UsageType::Agricultural);
+        assert_eq!(
+            import.drafts[0].outline(),
+            vec![Point::new(10.0, 53.5), Point::new(10.009, 53.5)]
+        );
+        assert_eq!(import.drafts[1].name, "placemark #1");
+        assert_eq!(import.drafts[1].usage, UsageType::Park);
+        assert_eq!(import.drafts[1].outline().len(), 4);
+        assert_eq!(import.notes.len(), 1);
+        assert_eq!(
+            import.notes[0].message,
+            "Line consists of 3 points, only the first and last one are used"
+        );
+    }
+
+    #[test]
+    fn unusable_placemarks_are_skipped() {
+        let import = import_kml(
+            r#"<kml xmlns="http://earth.google.com/kml/2.1">
+              <Placemark>
+                <name>Baum</name>
+                <ExtendedData><Data name="landuse"><value>meadow</value></Data></ExtendedData>
+                <Point><coordinates>10.0,53.5</coordinates></Point>
+              </Placemark>
+              <Placemark>
+                <ExtendedData><Data name="landuse"><value>meadow</value></Data></ExtendedData>
+                <LineString><coordinates>10.0,53.5 10.009</coordinates></LineString>
+              </Placemark>
+              <Placemark>
+                <ExtendedData><Data name="landuse"><value>meadow</value></Data></ExtendedData>
+                <Polygon>
+                  <outerBoundaryIs>
+                    <LinearRing>
+                      <coordinates>10.0,53.6 10.000005,53.6 10.000005,53.600005 10.0,53.6</coordinates>
+                    </LinearRing>
+                  </outerBoundaryIs>
+                </Polygon>
+              </Placemark>
+            </kml>"#,
+        )
+        .unwrap();
+
+        let notes = import
+            .notes
+            .iter()
+            .map(|note| (note.feature.as_str(), note.skipped, note.message.as_str()))
+            .collect::<Vec<_>>();
+
+        assert!(import.drafts.is_empty());
+        // Tuples with less than 2 values are dropped, leaving too few points
+        assert_eq!(
+            notes,
+            vec![
+                ("placemark #0 (Baum)", true, "Unsupported geometry: Point"),
+                ("placemark #1", true, "Line has less than 2 points"),
+                (
+                    "placemark #2",
+                    true,
+                    "Area does not contain any landable line"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use crate::{Location, SurfaceType, UsageType};
+use geo::Point;
+use std::collections::HashMap;
+
+mod geojson;
+mod kml;
+mod osm;
+
+pub use self::geojson::import_geojson;
+pub use kml::import_kml;
+#[cfg(feature = "pbf")]
+pub use osm::import_osm_pbf;
+pub use osm::import_osm_xml;
+
+/// Location drafts created from foreign geodata along with everything that needs to be reviewed
+#[derive(Debug, Default)]
+pub struct Import {
+    pub drafts: Vec<Location>,
+    pub notes: Vec<ImportNote>,
+}
+
+/// Remark about a single feature that could not be (fully) mapped
+#[derive(Debug, Clone)]
+pub struct ImportNote {
+    /// Human-readable label of the feature in the source data
+    pub feature: String,
+    /// Whether the feature has been left out of the drafts
+    pub skipped: bool,
+    pub message: String,
+}
+
+/// Feature as read from a foreign format, before it is turned into a location draft
+struct ForeignFeature {
+    label: String,
+    name: Option<String>,
+    tags: HashMap<String, String>,
+    geometry: ForeignGeometry,
+    /// Information that got lost while reading the feature
+    notes: Vec<String>,
+}
+
+enum ForeignGeometry {
+    Line(Vec<Point<f64>>),
+    Area(Vec<Point<f64>>),
+    Unsupported(String),
+    /// Geometry of a supported kind which can not be read
+    Invalid(String),
+}
+
+impl Import {
+    /// Serializes the drafts in the format of the location dataset
+    pub fn drafts_yaml(&self) -> Result<String, String> {
+        serde_yaml::to_string(&self.drafts).map_err(|e| e.to_string())
+    }
+
+    /// Human-readable listing of everything that could not be mapped
+    pub fn report(&self) -> String {
+        let skipped = self.notes.iter().filter(|n| n.skipped).count();
+        let mut report = format!(
+            "Created {} drafts, skipped {} features.\n\
+             Elevation, human presence and survey date have to be filled in for every draft!\n",
+            self.drafts.len(),
+            skipped
+        );
+
+        for note in self.notes.iter() {
+            let action = if note.skipped { "skipped" } else { "review" };
+            report.push_str(&format!(
+                "[{}] {}: {}\n",
+                action, note.feature, note.message
+            ));
+        }
+
+        report
+    }
+
+    fn note(&mut self, feature: &str, skipped: bool, message: String) {
+        self.notes.push(ImportNote {
+            feature: feature.to_string(),
+            skipped,
+            message,
+        });
+    }
+
+    /// Turns a foreign feature into a draft, noting anything that could not be mapped
+    fn add(&mut self, feature: ForeignFeature) {
+        let label = feature.label;
+
+        for message in feature.notes {
+            self.note(&label, false, message);
+        }
+
+        let surface = map_surface(&feature.tags);
+        let usage = map_usage(&feature.tags);
+
+        if surface.is_none() || usage.is_none() {
+            let missing = match (surface, usage) {
+                (None, None) => "surface and usage",
+                (None, _) => "surface",
+                _ => "usage",
+            };
+
+            self.note(
+                &label,
+                true,
+                format!(
+                    "Could not determine {} from tags {}",
+                    missing,
+                    format_tags(&feature.tags)
+                ),
+            );
+            return;
+        }
+
+        let (surface, usage) = (surface.unwrap(), usage.unwrap());
+        let name = feature.name.unwrap_or_else(|| label.clone());
+
+        let draft = match feature.geometry {
+            ForeignGeometry::Line(points) if points.len() >= 2 => {
+                if points.len() > 2 {
+                    self.note(
+                        &label,
+                        false,
+                        format!(
+                            "Line consists of {} points, only the first and last one are used",
+                            points.len()
+                        ),
+                    );
+                }
+
+                Location::draft_line(name, surface, usage, points[0], points[points.len() - 1])
+            }
+            ForeignGeometry::Area(mut points) => {
+                // Closed rings repeat their first point at the end
+                if points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                }
+
+                if points.len() < 3 {
+                    self.note(&label, true, String::from("Area has less than 3 points"));
+                    return;
+                }
+
+                match Location::draft_area(name, surface, usage, points) {
+                    Ok(draft) if draft.runways().is_empty() => {
+                        self.note(
+                            &label,
+                            true,
+                            String::from("Area does not contain any landable line"),
+                        );
+                        return;
+                    }
+                    Ok(draft) => draft,
+                    Err(message) => {
+                        self.note(&label, true, message);
+                        return;
+                    }
+                }
+            }
+            ForeignGeometry::Line(_) => {
+                self.note(&label, true, String::from("Line has less than 2 points"));
+                return;
+            }
+            ForeignGeometry::Unsupported(kind) => {
+                self.note(&label, true, format!("Unsupported geometry: {}", kind));
+                return;
+            }
+            ForeignGeometry::Invalid(message) => {
+                self.note(&label, true, message);
+                return;
+            }
+        };
+
+        self.drafts.push(draft);
+    }
+}
+
+fn format_tags(tags: &HashMap<String, String>) -> String {
+    let mut tags = tags
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>();
+    tags.sort();
+
+    format!("[{}]", tags.join(", "))
+}
+
+/// Derives the surface from explicit `surface` values or the kind of land use (OSM tagging conventions)
+fn map_surface(tags: &HashMap<String, String>) -> Option<SurfaceType> {
+    let tag = |key: &str| tags.get(key).map(String::as_str);
+
+    match tag("surface") {
+        Some(
+            "Asphalt" | "asphalt" | "concrete" | "concrete:plates" | "paved" | "paving_stones",
+        ) => return Some(SurfaceType::Asphalt),
+        Some("Gras" | "grass" | "grass_paver") => return Some(SurfaceType::Gras),
+        Some("Water" | "water") => return Some(SurfaceType::Water),
+        _ => {}
+    }
+
+    match (tag("landuse"), tag("leisure"), tag("natural")) {
+        (Some("meadow" | "farmland" | "grass" | "recreation_ground" | "village_green"), _, _)
+        | (_, Some("park" | "golf_course"), _)
+        | (_, _, Some("grassland" | "heath")) => Some(SurfaceType::Gras),
+        (Some("basin" | "reservoir"), _, _) | (_, _, Some("water")) => Some(SurfaceType::Water),
+        _ if tags.contains_key("water") => Some(SurfaceType::Water),
+        _ => None,
+    }
+}
+
+/// Derives the usage from explicit `usage` values or the OSM tags describing the purpose of the land
+fn map_usage(tags: &HashMap<String, String>) -> Option<UsageType> {
+    let tag = |key: &str| tags.get(key).map(String::as_str);
+
+    match tag("usage") {
+        Some("Agricultural") => return Some(UsageType::Agricultural),
+        Some("Aeronautical") => return Some(UsageType::Aeronautical),
+        Some("Nature") => return Some(UsageType::Nature),
+        Some("Waterway") => return Some(UsageType::Waterway),
+        Some("Event") => return Some(UsageType::Event),
+        Some("Park") => return Some(UsageType::Park),
+        _ => {}
+    }
+
+    match (
+        tag("aeroway"),
+        tag("landuse"),
+        tag("leisure"),
+        tag("natural"),
+    ) {
+        (Some("runway" | "taxiway" | "airstrip"), _, _, _) => Some(UsageType::Aeronautical),
+        (_, Some("meadow" | "farmland"), _, _) => Some(UsageType::Agricultural),
+        (_, Some("grass" | "recreation_ground" | "village_green"), _, _)
+        | (_, _, Some("park" | "golf_course"), _) => Some(UsageType::Park),
+        (_, _, Some("nature_reserve"), _) | (_, _, _, Some("grassland" | "heath")) => {
+            Some(UsageType::Nature)
+        }
+        (_, Some("basin" | "reservoir"), _, _) | (_, _, _, Some("water")) => {
+            Some(UsageType::Waterway)
+        }
+        _ if tags.contains_key("water") => Some(UsageType::Waterway),
+        _ if tag("amenity") == Some("festival_grounds") => Some(UsageType::Event),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Meadow feature with the given geometry
+    fn feature(geometry: ForeignGeometry) -> ForeignFeature {
+        ForeignFeature {
+            label: String::from("feature #0"),
+            name: Some(String::from("Wiese")),
+            tags: tags(&[("landuse", "meadow")]),
+            geometry,
+            notes: vec![],
+        }
+    }
+
+    /// Closed ring around a square with the given side length (in degrees)
+    fn square(size: f64) -> Vec<Point<f64>> {
+        vec![
+            Point::new(10.0, 53.5),
+            Point::new(10.0 + size, 53.5),
+            Point::new(10.0 + size, 53.5 + size),
+            Point::new(10.0, 53.5 + size),
+            Point::new(10.0, 53.5),
+        ]
+    }
+
+    fn messages(import: &Import) -> Vec<(bool, &str)> {
+        import
+            .notes
+            .iter()
+            .map(|note| (note.skipped, note.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn surface_mapping() {
+        let surface = |pairs: &[(&str, &str)]| map_surface(&tags(pairs));
+
+        assert_eq!(
+            surface(&[("surface", "Asphalt")]),
+            Some(SurfaceType::Asphalt)
+        );
+        assert_eq!(
+            surface(&[("surface", "concrete")]),
+            Some(SurfaceType::Asphalt)
+        );
+        assert_eq!(surface(&[("surface", "grass")]), Some(SurfaceType::Gras));
+        assert_eq!(surface(&[("landuse", "farmland")]), Some(SurfaceType::Gras));
+        assert_eq!(
+            surface(&[("leisure", "golf_course")]),
+            Some(SurfaceType::Gras)
+        );
+        assert_eq!(surface(&[("natural", "heath")]), Some(SurfaceType::Gras));
+        assert_eq!(
+            surface(&[("landuse", "reservoir")]),
+            Some(SurfaceType::Water)
+        );
+        assert_eq!(surface(&[("water", "lake")]), Some(SurfaceType::Water));
+        // Explicit surfaces take precedence over the land use
+        assert_eq!(
+            surface(&[("surface", "asphalt"), ("landuse", "meadow")]),
+            Some(SurfaceType::Asphalt)
+        );
+        assert_eq!(surface(&[("surface", "gravel")]), None);
+        assert_eq!(surface(&[("landuse", "residential")]), None);
+    }
+
+    #[test]
+    fn usage_mapping() {
+        let usage = |pairs: &[(&str, &str)]| map_usage(&tags(pairs));
+
+        assert_eq!(usage(&[("usage", "Event")]), Some(UsageType::Event));
+        assert_eq!(
+            usage(&[("aeroway", "runway")]),
+            Some(UsageType::Aeronautical)
+        );
+        assert_eq!(
+            usage(&[("landuse", "meadow")]),
+            Some(UsageType::Agricultural)
+        );
+        assert_eq!(usage(&[("leisure", "park")]), Some(UsageType::Park));
+        assert_eq!(
+            usage(&[("landuse", "village_green")]),
+            Some(UsageType::Park)
+        );
+        assert_eq!(
+            usage(&[("leisure", "nature_reserve")]),
+            Some(UsageType::Nature)
+        );
+        assert_eq!(usage(&[("natural", "water")]), Some(UsageType::Waterway));
+        assert_eq!(usage(&[("water", "river")]), Some(UsageType::Waterway));
+        assert_eq!(
+            usage(&[("amenity", "festival_grounds")]),
+            Some(UsageType::Event)
+        );
+        // Runways on farmland are still runways
+        assert_eq!(
+            usage(&[("aeroway", "runway"), ("landuse", "farmland")]),
+            Some(UsageType::Aeronautical)
+        );
+        assert_eq!(usage(&[("usage", "Parking")]), None);
+        assert_eq!(usage(&[("landuse", "residential")]), None);
+    }
+
+    #[test]
+    fn lines_become_drafts() {
+        let mut import = Import::default();
+        import.add(feature(ForeignGeometry::Line(vec![
+            Point::new(10.0, 53.5),
+            Point::new(10.004, 53.5),
+            Point::new(10.009, 53.5),
+        ])));
+
+        assert_eq!(import.drafts.len(), 1);
+        assert_eq!(import.drafts[0].name, "Wiese");
+        assert_eq!(import.drafts[0].surface, SurfaceType::Gras);
+        assert_eq!(import.drafts[0].usage, UsageType::Agricultural);
+        assert_eq!(
+            import.drafts[0].outline(),
+            vec![Point::new(10.0, 53.5), Point::new(10.009, 53.5)]
+        );
+        assert_eq!(
+            messages(&import),
+            vec![(
+                false,
+                "Line consists of 3 points, only the first and last one are used"
+            )]
+        );
+    }
+
+    #[test]
+    fn closed_rings_become_areas() {
+        let mut import = Import::default();
+        import.add(feature(ForeignGeometry::Area(square(0.005))));
+
+        assert_eq!(import.drafts.len(), 1);
+        assert!(import.drafts[0].area().is_some());
+        // The repeated first point is dropped
+        assert_eq!(import.drafts[0].outline(), square(0.005)[..4].to_vec());
+        assert!(!import.drafts[0].runways().is_empty());
+        assert!(import.notes.is_empty());
+    }
+
+    #[test]
+    fn unusable_features_are_skipped() {
+        let mut import = Import::default();
+
+        import.add(feature(ForeignGeometry::Line(vec![Point::new(10.0, 53.5)])));
+        // Closed ring of a single line
+        import.add(feature(ForeignGeometry::Area(vec![
+            Point::new(10.0, 53.5),
+            Point::new(10.005, 53.5),
+            Point::new(10.0, 53.5),
+        ])));
+        // Sides of about half a meter
+        import.add(feature(ForeignGeometry::Area(square(0.000005))));
+        import.add(feature(ForeignGeometry::Unsupported(String::from("Point"))));
+        import.add(feature(ForeignGeometry::Invalid(String::from(
+            "Position has less than 2 values",
+        ))));
+        import.add(ForeignFeature {
+            tags: tags(&[("landuse", "residential")]),
+            ..feature(ForeignGeometry::Area(square(0.005)))
+        });
+        import.add(ForeignFeature {
+            tags: tags(&[("surface", "grass")]),
+            ..feature(ForeignGeometry::Area(square(0.005)))
+        });
+
+        assert!(import.drafts.is_empty());
+        assert_eq!(
+            messages(&import),
+            vec![
+                (true, "Line has less than 2 points"),
+                (true, "Area has less than 3 points"),
+                (true, "Area does not contain any landable line"),
+                (true, "Unsupported geometry: Point"),
+                (true, "Position has less than 2 values"),
+                (
+                    true,
+                    "Could not determine surface and usage from tags [landuse=residential]"
+                ),
+                (true, "Could not determine usage from tags [surface=grass]"),
+            ]
+        );
+    }
+}
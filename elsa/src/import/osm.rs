@@ -0,0 +1,257 @@
+use super::{map_usage, ForeignFeature, ForeignGeometry, Import};
+use geo::Point;
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+
+/// Raw contents of an OSM extract, independent of the file format
+#[derive(Default)]
+struct OsmData {
+    nodes: HashMap<i64, Point<f64>>,
+    ways: Vec<(i64, Vec<i64>, HashMap<String, String>)>,
+    relations: Vec<(i64, HashMap<String, String>)>,
+}
+
+/// Creates location drafts from the ways of an OSM XML extract which describe landable surfaces,
+/// for example `aeroway=runway` or `landuse=meadow`. Everything else within the extract is ignored.
+pub fn import_osm_xml(source: &str) -> Result<Import, String> {
+    let document = Document::parse(source).map_err(|e| e.to_string())?;
+    let mut data = OsmData::default();
+
+    let id = |node: &Node| node.attribute("id").and_then(|id| id.parse::<i64>().ok());
+    let tags = |node: &Node| {
+        node.children()
+            .filter(|child| child.has_tag_name("tag"))
+            .filter_map(|tag| Some((tag.attribute("k")?.into(), tag.attribute("v")?.into())))
+            .collect::<HashMap<String, String>>()
+    };
+
+    for element in document.root_element().children() {
+        match element.tag_name().name() {
+            "node" => {
+                let coordinate = |name| element.attribute(name).and_then(|v| v.parse().ok());
+
+                if let (Some(id), Some(lat), Some(lon)) =
+                    (id(&element), coordinate("lat"), coordinate("lon"))
+                {
+                    data.nodes.insert(id, Point::new(lon, lat));
+                }
+            }
+            "way" => {
+                let nodes = element
+                    .children()
+                    .filter(|child| child.has_tag_name("nd"))
+                    .filter_map(|nd| nd.attribute("ref").and_then(|r| r.parse().ok()))
+                    .collect();
+
+                if let Some(id) = id(&element) {
+                    data.ways.push((id, nodes, tags(&element)));
+                }
+            }
+            "relation" => {
+                if let Some(id) = id(&element) {
+                    data.relations.push((id, tags(&element)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(import_osm(data))
+}
+
+/// Same as `import_osm_xml` but for the binary PBF format
+#[cfg(feature = "pbf")]
+pub fn import_osm_pbf<R: std::io::Read + std::io::Seek>(reader: R) -> Result<Import, String> {
+    use osmpbfreader::{OsmObj, OsmPbfReader};
+
+    let tags = |tags: &osmpbfreader::Tags| {
+        tags.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<String, String>>()
+    };
+
+    // Only load relevant ways and relations along with the nodes they reference
+    let objects = OsmPbfReader::new(reader)
+        .get_objs_and_deps(|object| !object.is_node() && map_usage(&tags(object.tags())).is_some())
+        .map_err(|e| e.to_string())?;
+
+    let mut data = OsmData::default();
+
+    for object in objects.values() {
+        match object {
+            OsmObj::Node(node) => {
+                data.nodes
+                    .insert(node.id.0, Point::new(node.lon(), node.lat()));
+            }
+            OsmObj::Way(way) => {
+                let nodes = way.nodes.iter().map(|node| node.0).collect();
+                data.ways.push((way.id.0, nodes, tags(&way.tags)));
+            }
+            OsmObj::Relation(relation) => {
+                data.relations.push((relation.id.0, tags(&relation.tags)));
+            }
+        }
+    }
+
+    Ok(import_osm(data))
+}
+
+fn import_osm(data: OsmData) -> Import {
+    let mut import = Import::default();
+
+    for (id, nodes, tags) in data.ways {
+        if map_usage(&tags).is_none() {
+            continue;
+        }
+
+        let label = format!("way {}", id);
+        let points = nodes
+            .iter()
+            .filter_map(|node| data.nodes.get(node).copied())
+            .collect::<Vec<_>>();
+
+        if points.len() < nodes.len() {
+            import.note(
+                &label,
+                true,
+                String::from("References nodes which are not part of the extract"),
+            );
+            continue;
+        }
+
+        // Closed ways describe areas, with the exception of runways which are mapped as their centerline
+        let closed = nodes.len() > 3 && nodes.first() == nodes.last();
+        let runway = tags.get("aeroway").map(String::as_str) == Some("runway");
+        let geometry = if closed && !runway {
+            ForeignGeometry::Area(points)
+        } else {
+            ForeignGeometry::Line(points)
+        };
+
+        let name = tags.get("name").or_else(|| tags.get("ref")).cloned();
+
+        import.add(ForeignFeature {
+            label,
+            name,
+            tags,
+            geometry,
+            notes: vec![],
+        });
+    }
+
+    for (id, tags) in data.relations {
+        if map_usage(&tags).is_some() {
+            import.note(
+                &format!("relation {}", id),
+                true,
+                String::from("Relations are not supported, the area has to be traced manually"),
+            );
+        }
+    }
+
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UsageType;
+
+    #[test]
+    fn ways_are_mapped() {
+        let import = import_osm_xml(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <osm version="0.6">
+              <node id="1" lat="53.5" lon="10.0"/>
+              <node id="2" lat="53.5" lon="10.009"/>
+              <node id="3" lat="53.6" lon="10.0"/>
+              <node id="4" lat="53.6" lon="10.005"/>
+              <node id="5" lat="53.605" lon="10.005"/>
+              <node id="6" lat="53.605" lon="10.0"/>
+              <way id="10">
+                <nd ref="1"/><nd ref="2"/>
+                <tag k="aeroway" v="runway"/><tag k="surface" v="grass"/><tag k="ref" v="09/27"/>
+              </way>
+              <way id="11">
+                <nd ref="3"/><nd ref="4"/><nd ref="5"/><nd ref="6"/><nd ref="3"/>
+                <tag k="landuse" v="meadow"/><tag k="name" v="Wiese"/>
+              </way>
+              <way id="12">
+                <nd ref="1"/><nd ref="3"/>
+                <tag k="highway" v="track"/>
+              </way>
+            </osm>"#,
+        )
+        .unwrap();
+
+        assert_eq!(import.drafts.len(), 2);
+        assert_eq!(import.drafts[0].name, "09/27");
+        assert_eq!(import.drafts[0].usage, UsageType::Aeronautical);
+        assert!(import.drafts[0].area().is_none());
+        assert_eq!(import.drafts[1].name, "Wiese");
+        assert_eq!(
+            import.drafts[1].outline(),
+            vec![
+                Point::new(10.0, 53.6),
+                Point::new(10.005, 53.6),
+                Point::new(10.005, 53.605),
+                Point::new(10.0, 53.605)
+            ]
+        );
+        // Unrelated ways are ignored without a note
+        assert!(import.notes.is_empty());
+    }
+
+    #[test]
+    fn unusable_ways_are_skipped() {
+        let import = import_osm_xml(
+            r#"<osm version="0.6">
+              <node id="1" lat="53.5" lon="10.0"/>
+              <node id="2" lat="53.5" lon="10.000005"/>
+              <node id="3" lat="53.500005" lon="10.000005"/>
+              <way id="10">
+                <nd ref="1"/><nd ref="4"/>
+                <tag k="landuse" v="meadow"/>
+              </way>
+              <way id="11">
+                <nd ref="1"/>
+                <tag k="landuse" v="meadow"/>
+              </way>
+              <way id="12">
+                <nd ref="1"/><nd ref="2"/><nd ref="3"/><nd ref="1"/>
+                <tag k="landuse" v="meadow"/>
+              </way>
+              <relation id="20">
+                <member type="way" ref="12" role="outer"/>
+                <tag k="landuse" v="meadow"/>
+              </relation>
+            </osm>"#,
+        )
+        .unwrap();
+
+        let notes = import
+            .notes
+            .iter()
+            .map(|note| (note.feature.as_str(), note.skipped, note.message.as_str()))
+            .collect::<Vec<_>>();
+
+        assert!(import.drafts.is_empty());
+        assert_eq!(
+            notes,
+            vec![
+                (
+                    "way 10",
+                    true,
+                    "References nodes which are not part of the extract"
+                ),
+                ("way 11", true, "Line has less than 2 points"),
+                ("way 12", true, "Area does not contain any landable line"),
+                (
+                    "relation 20",
+                    true,
+                    "Relations are not supported, the area has to be traced manually"
+                ),
+            ]
+        );
+    }
+}
@@ -4,9 +4,19 @@ mod calculator;
 mod data;
 mod dubin;
 mod helpers;
+mod import;
+mod lint;
+mod raster;
+mod rules;
+mod terrain;
 
 pub use calculator::*;
 pub use data::*;
+pub use import::*;
+pub use lint::*;
+pub use raster::*;
+pub use rules::*;
+pub use terrain::*;
 
 #[wasm_bindgen(start)]
 pub fn startup() {
@@ -0,0 +1,515 @@
+use crate::{
+    HumanPresenceCategory, Location, LocationMap, Parser, UsageType, HAZARD_CORRIDOR_WIDTH,
+};
+use chrono::Utc;
+use geo::{prelude::Intersects, Line, Polygon};
+use std::{collections::HashMap, fmt};
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser as YamlParser},
+    scanner::Marker,
+};
+
+/// Highest point on earth (in feet), anything above can not be right
+const MAXIMUM_ELEVATION: u32 = 29_032;
+/// Landing lines shorter than this (in meters) are considered to have zero length
+const MINIMUM_LINE_LENGTH: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Problem found within the location dataset
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Line in the source file (starting at 1) to which the issue relates, if known
+    pub line: Option<usize>,
+    /// Name of the affected location, if any
+    pub location: Option<String>,
+    pub message: String,
+}
+
+/// Collects the lines on which the entries of the top-level sequence start
+#[derive(Default)]
+struct EntryLines {
+    depth: usize,
+    lines: Vec<usize>,
+}
+
+/// Geometry of a location used to detect overlaps
+enum Shape {
+    Line(Line<f64>),
+    Area(Polygon<f64>),
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.severity)?;
+
+        if let Some(location) = &self.location {
+            write!(f, " [{}]", location)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl MarkedEventReceiver for EntryLines {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::SequenceStart(_) | Event::MappingStart(_) => {
+                if self.depth == 1 {
+                    self.lines.push(mark.line());
+                }
+                self.depth += 1;
+            }
+            Event::SequenceEnd | Event::MappingEnd => self.depth -= 1,
+            Event::Scalar(..) | Event::Alias(_) if self.depth == 1 => self.lines.push(mark.line()),
+            _ => {}
+        }
+    }
+}
+
+impl Shape {
+    fn of(location: &Location) -> Self {
+        match location.area() {
+            Some(area) => Shape::Area(area),
+            None => {
+                let outline = location.outline();
+                Shape::Line(Line::new(outline[0], outline[1]))
+            }
+        }
+    }
+
+    fn intersects(&self, other: &Shape) -> bool {
+        match (self, other) {
+            (Shape::Line(a), Shape::Line(b)) => a.intersects(b),
+            (Shape::Line(a), Shape::Area(b)) => b.intersects(a),
+            (Shape::Area(a), Shape::Line(b)) => a.intersects(b),
+            (Shape::Area(a), Shape::Area(b)) => a.intersects(b),
+        }
+    }
+}
+
+/// Checks the YAML source of the location dataset for mistakes.
+/// Issues are ordered by their position within the file.
+pub fn lint_locations(yaml: &str) -> Vec<Issue> {
+    let locations = match serde_yaml::from_str::<Vec<Location>>(yaml) {
+        Ok(locations) => locations,
+        Err(error) => {
+            return vec![Issue {
+                severity: Severity::Error,
+                line: error.location().map(|l| l.line()),
+                location: None,
+                message: error.to_string(),
+            }]
+        }
+    };
+
+    let lines = entry_lines(yaml);
+    let lines = if lines.len() == locations.len() {
+        lines.into_iter().map(Some).collect()
+    } else {
+        vec![None; locations.len()]
+    };
+
+    let mut issues = Vec::new();
+    let mut report = |index: usize, severity: Severity, message: String| {
+        issues.push(Issue {
+            severity,
+            line: lines[index],
+            location: Some(locations[index].name.clone()),
+            message,
+        })
+    };
+
+    for (index, error) in Parser::location_errors(&locations) {
+        report(index, Severity::Error, error);
+    }
+
+    for (index, location) in locations.iter().enumerate() {
+        for (severity, message) in lint_location(location) {
+            report(index, severity, message);
+        }
+    }
+
+    // Locations which share coordinates have most likely been copied without being adjusted
+    let mut coordinates: HashMap<(u64, u64), usize> = HashMap::new();
+    for (index, location) in locations.iter().enumerate() {
+        let mut points = location.outline();
+        points.dedup();
+
+        for point in points {
+            let key = (point.x().to_bits(), point.y().to_bits());
+
+            match coordinates.get(&key) {
+                Some(&other) if other != index => report(
+                    index,
+                    Severity::Error,
+                    format!(
+                        "Shares the coordinate [{}, {}] with '{}'",
+                        point.y(),
+                        point.x(),
+                        locations[other].name
+                    ),
+                ),
+                Some(_) => {}
+                None => {
+                    coordinates.insert(key, index);
+                }
+            }
+        }
+    }
+
+    // Overlapping locations are reported once for the latter of the two. Crossing runways are common
+    // at airfields, and locations without any landable line have no meaningful geometry, so both are skipped.
+    let landable = |location: &&Location| !location.runways().is_empty();
+    let indices = locations
+        .iter()
+        .enumerate()
+        .map(|(index, location)| (location.id(), index))
+        .collect::<HashMap<_, _>>();
+    let shapes = locations.iter().map(Shape::of).collect::<Vec<_>>();
+    let map = LocationMap::new(locations.iter().filter(landable).cloned().collect());
+
+    for (index, location) in locations.iter().enumerate().filter(|(_, l)| landable(l)) {
+        for other in map.within_bounds(&location.bounding_rect()) {
+            let other = indices[&other.id()];
+
+            let aeronautical = location.usage == UsageType::Aeronautical
+                && locations[other].usage == UsageType::Aeronautical;
+
+            if other < index && !aeronautical && shapes[index].intersects(&shapes[other]) {
+                report(
+                    index,
+                    Severity::Warning,
+                    format!("Overlaps with '{}'", locations[other].name),
+                );
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// Lines (starting at 1) of the locations within the YAML source, regardless of their indentation
+fn entry_lines(yaml: &str) -> Vec<usize> {
+    let mut entries = EntryLines::default();
+
+    match YamlParser::new(yaml.chars()).load(&mut entries, false) {
+        Ok(()) => entries.lines,
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Issues that can be determined by looking at a single location
+fn lint_location(location: &Location) -> Vec<(Severity, String)> {
+    let mut issues = Vec::new();
+
+    for runway in location.runways() {
+        if runway.length() < MINIMUM_LINE_LENGTH {
+            issues.push((
+                Severity::Error,
+                String::from("Landing line has zero length"),
+            ));
+        }
+    }
+
+    let outline = location.outline();
+    if location.area().is_some() && outline.windows(2).any(|pair| pair[0] == pair[1]) {
+        issues.push((
+            Severity::Warning,
+            String::from("Polygon contains consecutive duplicate points"),
+        ));
+    }
+
+    if location.elevation > MAXIMUM_ELEVATION {
+        issues.push((
+            Severity::Error,
+            format!(
+                "Elevation of {}ft is higher than any point on earth",
+                location.elevation
+            ),
+        ));
+    }
+
+    if location.usage == UsageType::Aeronautical
+        && location.human_presence == HumanPresenceCategory::Dense
+    {
+        issues.push((
+            Severity::Warning,
+            String::from("Aeronautical locations are not expected to be densely crowded"),
+        ));
+    }
+
+    if !location.crops.is_empty() && location.usage != UsageType::Agricultural {
+        issues.push((
+            Severity::Warning,
+            String::from("Crops are only expected on agricultural locations"),
+        ));
+    }
+
+    if !location.survey_date.is_empty() {
+        match location.surveyed_at() {
+            Some(date) if date > Utc::now() => issues.push((
+                Severity::Error,
+                format!("Survey date '{}' lies in the future", location.survey_date),
+            )),
+            Some(_) => {}
+            None => issues.push((
+                Severity::Error,
+                format!("Survey date '{}' is malformed", location.survey_date),
+            )),
+        }
+    }
+
+    // Hazards are expected beyond the end of one-way locations, not underneath the approach.
+    // The direction can only be checked against recorded hazards, lines without any pass unnoticed.
+    let one_way_line = location
+        .primary_runway()
+        .filter(|runway| location.area().is_none() && !runway.reversible);
+
+    if let Some(runway) = one_way_line {
+        let length = runway.length();
+        let (approach, departure) = location
+            .hazards
+            .iter()
+            .map(|hazard| (runway.relative_position(hazard.position()), hazard.height()))
+            .filter(|((_, across), _)| across.abs() <= HAZARD_CORRIDOR_WIDTH)
+            .fold(
+                (0.0, 0.0),
+                |(approach, departure): (f64, f64), ((along, _), height)| {
+                    if along < length / 2.0 {
+                        (approach.max(height), departure)
+                    } else {
+                        (approach, departure.max(height))
+                    }
+                },
+            );
+
+        if approach > departure {
+            issues.push((
+                Severity::Warning,
+                String::from(
+                    "Approach leads over the highest hazards, start and end might be swapped",
+                ),
+            ));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Agricultural location with a reversible line between the given coordinates, followed by additional fields
+    fn entry(name: &str, start: (f64, f64), end: (f64, f64), fields: &str) -> String {
+        format!(
+            "- name: {}
+  elevation: 30
+  reversible: true
+  surface: Gras
+  usage: Agricultural
+  humanPresence: Unlikely
+  coordinates:
+    start: [{}, {}]
+    end: [{}, {}]
+  surveyDate: 2021-11-21T15:42:56+0000
+{}",
+            name, start.0, start.1, end.0, end.1, fields
+        )
+    }
+
+    /// West-east line of about 600m at the given latitude
+    fn field(name: &str, latitude: f64) -> String {
+        entry(name, (latitude, 10.0), (latitude, 10.009), "")
+    }
+
+    /// Messages of all issues reported for the location with the given name
+    fn messages(yaml: &str, name: &str) -> Vec<String> {
+        lint_locations(yaml)
+            .into_iter()
+            .filter(|issue| issue.location.as_deref() == Some(name))
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn issues_point_to_entries() {
+        let yaml = "
+  # Sequence entries do not need to start at the beginning of a line
+  - name: Feld
+    elevation: 30000
+    reversible: true
+    surface: Gras
+    usage: Agricultural
+    humanPresence: Unlikely
+    coordinates: { start: [53.5, 10.0], end: [53.5, 10.009] }
+    surveyDate: 2021-11-21T15:42:56+0000
+
+  -
+    name: Wiese
+    elevation: 30000
+    reversible: true
+    surface: Gras
+    usage: Agricultural
+    humanPresence: Unlikely
+    coordinates: { start: [53.6, 10.0], end: [53.6, 10.009] }
+    surveyDate: 2021-11-21T15:42:56+0000
+";
+        let lines = lint_locations(yaml)
+            .into_iter()
+            .map(|issue| (issue.line, issue.location.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                (Some(3), String::from("Feld")),
+                (Some(13), String::from("Wiese"))
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_coordinates() {
+        let yaml = field("Feld", 53.5) + &entry("Wiese", (53.5, 10.0), (53.6, 10.0), "");
+
+        assert!(messages(&yaml, "Feld").is_empty());
+        assert!(messages(&yaml, "Wiese").contains(&String::from(
+            "Shares the coordinate [53.5, 10] with 'Feld'"
+        )));
+    }
+
+    #[test]
+    fn overlapping_locations() {
+        let crossing = entry("Wiese", (53.499, 10.004), (53.501, 10.004), "");
+        let yaml = field("Feld", 53.5) + &crossing;
+
+        assert!(messages(&yaml, "Feld").is_empty());
+        assert_eq!(
+            messages(&yaml, "Wiese"),
+            vec![String::from("Overlaps with 'Feld'")]
+        );
+
+        // Crossing runways are common at airfields
+        let yaml = yaml.replace("usage: Agricultural", "usage: Aeronautical");
+
+        assert!(messages(&yaml, "Wiese").is_empty());
+
+        // Separate locations do not overlap
+        let yaml = field("Feld", 53.5) + &field("Wiese", 53.6);
+
+        assert!(messages(&yaml, "Wiese").is_empty());
+    }
+
+    #[test]
+    fn zero_length_line() {
+        let yaml = entry("Feld", (53.5, 10.0), (53.5, 10.0), "");
+
+        assert!(messages(&yaml, "Feld").contains(&String::from("Landing line has zero length")));
+        assert!(messages(&field("Feld", 53.5), "Feld").is_empty());
+    }
+
+    #[test]
+    fn elevation_above_highest_point() {
+        let highest = field("Feld", 53.5).replace("elevation: 30", "elevation: 29032");
+        let above = field("Feld", 53.5).replace("elevation: 30", "elevation: 29033");
+
+        assert!(messages(&highest, "Feld").is_empty());
+        assert_eq!(
+            messages(&above, "Feld"),
+            vec![String::from(
+                "Elevation of 29033ft is higher than any point on earth"
+            )]
+        );
+    }
+
+    #[test]
+    fn densely_crowded_airfield() {
+        let yaml = field("Feld", 53.5)
+            .replace("usage: Agricultural", "usage: Aeronautical")
+            .replace("humanPresence: Unlikely", "humanPresence: Dense");
+
+        assert_eq!(
+            messages(&yaml, "Feld"),
+            vec![String::from(
+                "Aeronautical locations are not expected to be densely crowded"
+            )]
+        );
+        assert!(messages(&yaml.replace("Aeronautical", "Park"), "Feld").is_empty());
+    }
+
+    #[test]
+    fn crops_on_non_agricultural_location() {
+        let crops = "  crops:\n    - { crop: Mais, season: { from: '04-15', until: '10-15' } }\n";
+        let yaml = entry("Feld", (53.5, 10.0), (53.5, 10.009), crops);
+
+        assert!(messages(&yaml, "Feld").is_empty());
+        assert_eq!(
+            messages(&yaml.replace("usage: Agricultural", "usage: Park"), "Feld"),
+            vec![String::from(
+                "Crops are only expected on agricultural locations"
+            )]
+        );
+    }
+
+    #[test]
+    fn invalid_survey_dates() {
+        let future = field("Feld", 53.5).replace("2021-11-21", "2999-11-21");
+        let malformed = field("Feld", 53.5).replace("2021-11-21T15:42:56+0000", "2021-11-21");
+
+        assert_eq!(
+            messages(&future, "Feld"),
+            vec![String::from(
+                "Survey date '2999-11-21T15:42:56+0000' lies in the future"
+            )]
+        );
+        assert_eq!(
+            messages(&malformed, "Feld"),
+            vec![String::from("Survey date '2021-11-21' is malformed")]
+        );
+    }
+
+    #[test]
+    fn hazards_underneath_approach() {
+        let one_way = |height: f64, longitude: f64| {
+            entry(
+                "Feld",
+                (53.5, 10.0),
+                (53.5, 10.009),
+                &format!(
+                    "  hazards:\n    - {{ type: Trees, position: [53.5, {}], height: {} }}\n",
+                    longitude, height
+                ),
+            )
+            .replace("reversible: true", "reversible: false")
+        };
+        let swapped = "Approach leads over the highest hazards, start and end might be swapped";
+
+        assert_eq!(
+            messages(&one_way(80.0, 10.0005), "Feld"),
+            vec![String::from(swapped)]
+        );
+        assert!(messages(&one_way(80.0, 10.0085), "Feld").is_empty());
+
+        // Reversible lines can be landed in the other direction
+        let reversible = one_way(80.0, 10.0005).replace("reversible: false", "reversible: true");
+
+        assert!(messages(&reversible, "Feld").is_empty());
+    }
+}
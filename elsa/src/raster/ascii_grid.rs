@@ -0,0 +1,38 @@
+use super::{AltitudeRaster, NO_DATA};
+
+/// Writes an ESRI ASCII grid, cells that are not square are described by separate `dx` and `dy` headers
+pub(super) fn write_ascii_grid(raster: &AltitudeRaster) -> String {
+    let mut grid = format!(
+        "ncols {}\nnrows {}\nxllcorner {}\nyllcorner {}\n",
+        raster.columns,
+        raster.rows,
+        raster.west,
+        raster.north - raster.rows as f64 * raster.dy
+    );
+
+    if raster.dx == raster.dy {
+        grid.push_str(&format!("cellsize {}\n", raster.dx));
+    } else {
+        grid.push_str(&format!("dx {}\ndy {}\n", raster.dx, raster.dy));
+    }
+
+    grid.push_str(&format!("nodata_value {}\n", NO_DATA));
+
+    for row in raster.values.chunks(raster.columns) {
+        let values = row
+            .iter()
+            .map(|value| {
+                if value.is_nan() {
+                    NO_DATA.to_string()
+                } else {
+                    format!("{:.1}", value)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        grid.push_str(&values.join(" "));
+        grid.push('\n');
+    }
+
+    grid
+}
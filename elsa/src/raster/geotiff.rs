@@ -0,0 +1,175 @@
+use super::{AltitudeRaster, NO_DATA};
+
+const IMAGE_WIDTH: u16 = 256;
+const IMAGE_LENGTH: u16 = 257;
+const BITS_PER_SAMPLE: u16 = 258;
+const COMPRESSION: u16 = 259;
+const PHOTOMETRIC_INTERPRETATION: u16 = 262;
+const STRIP_OFFSETS: u16 = 273;
+const SAMPLES_PER_PIXEL: u16 = 277;
+const ROWS_PER_STRIP: u16 = 278;
+const STRIP_BYTE_COUNTS: u16 = 279;
+const PLANAR_CONFIGURATION: u16 = 284;
+const SAMPLE_FORMAT: u16 = 339;
+const MODEL_PIXEL_SCALE: u16 = 33550;
+const MODEL_TIEPOINT: u16 = 33922;
+const GEO_KEY_DIRECTORY: u16 = 34735;
+const GDAL_NODATA: u16 = 42113;
+
+const MODEL_TYPE_GEO_KEY: u16 = 1024;
+const RASTER_TYPE_GEO_KEY: u16 = 1025;
+const GEOGRAPHIC_TYPE_GEO_KEY: u16 = 2048;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const RASTER_PIXEL_IS_AREA: u16 = 1;
+const GCS_WGS_84: u16 = 4326;
+
+const TYPE_ASCII: u16 = 2;
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_DOUBLE: u16 = 12;
+
+/// Directory entry with its values already encoded as little endian bytes
+struct Entry {
+    tag: u16,
+    kind: u16,
+    count: usize,
+    data: Vec<u8>,
+}
+
+impl Entry {
+    fn shorts(tag: u16, values: &[u16]) -> Self {
+        Self {
+            tag,
+            kind: TYPE_SHORT,
+            count: values.len(),
+            data: values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    fn long(tag: u16, value: u32) -> Self {
+        Self {
+            tag,
+            kind: TYPE_LONG,
+            count: 1,
+            data: value.to_le_bytes().to_vec(),
+        }
+    }
+
+    fn doubles(tag: u16, values: &[f64]) -> Self {
+        Self {
+            tag,
+            kind: TYPE_DOUBLE,
+            count: values.len(),
+            data: values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    fn text(tag: u16, text: &str) -> Self {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+
+        Self {
+            tag,
+            kind: TYPE_ASCII,
+            count: data.len(),
+            data,
+        }
+    }
+}
+
+/// Writes a little endian GeoTIFF with the altitudes as a single uncompressed strip of 32-bit floats.
+/// The image data directly follows the header, the directory and any values too large for it come last.
+pub(super) fn write_geotiff(raster: &AltitudeRaster) -> Vec<u8> {
+    let image = raster
+        .values
+        .iter()
+        .map(|value| if value.is_nan() { NO_DATA } else { *value })
+        .flat_map(|value| value.to_le_bytes())
+        .collect::<Vec<_>>();
+    let image_offset = 8;
+    let directory_offset = image_offset + image.len();
+
+    let entries = [
+        Entry::long(IMAGE_WIDTH, raster.columns as u32),
+        Entry::long(IMAGE_LENGTH, raster.rows as u32),
+        Entry::shorts(BITS_PER_SAMPLE, &[32]),
+        Entry::shorts(COMPRESSION, &[1]),
+        // Black is zero
+        Entry::shorts(PHOTOMETRIC_INTERPRETATION, &[1]),
+        Entry::long(STRIP_OFFSETS, image_offset as u32),
+        Entry::shorts(SAMPLES_PER_PIXEL, &[1]),
+        Entry::long(ROWS_PER_STRIP, raster.rows as u32),
+        Entry::long(STRIP_BYTE_COUNTS, image.len() as u32),
+        Entry::shorts(PLANAR_CONFIGURATION, &[1]),
+        // Floating point
+        Entry::shorts(SAMPLE_FORMAT, &[3]),
+        Entry::doubles(MODEL_PIXEL_SCALE, &[raster.dx, raster.dy, 0.0]),
+        Entry::doubles(
+            MODEL_TIEPOINT,
+            &[0.0, 0.0, 0.0, raster.west, raster.north, 0.0],
+        ),
+        // Header (version, revision, minor revision, key count) followed by keys of (id, location, count, value)
+        Entry::shorts(
+            GEO_KEY_DIRECTORY,
+            &[
+                1,
+                1,
+                0,
+                3,
+                MODEL_TYPE_GEO_KEY,
+                0,
+                1,
+                MODEL_TYPE_GEOGRAPHIC,
+                RASTER_TYPE_GEO_KEY,
+                0,
+                1,
+                RASTER_PIXEL_IS_AREA,
+                GEOGRAPHIC_TYPE_GEO_KEY,
+                0,
+                1,
+                GCS_WGS_84,
+            ],
+        ),
+        Entry::text(GDAL_NODATA, &NO_DATA.to_string()),
+    ];
+
+    let mut bytes = b"II".to_vec();
+    bytes.extend_from_slice(&42u16.to_le_bytes());
+    bytes.extend_from_slice(&(directory_offset as u32).to_le_bytes());
+    bytes.extend_from_slice(&image);
+
+    // Values that do not fit into an entry are stored after the directory, starting on a word boundary
+    let overflow_offset = directory_offset + 2 + entries.len() * 12 + 4;
+    let mut overflow = Vec::new();
+
+    bytes.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for entry in entries.iter() {
+        bytes.extend_from_slice(&entry.tag.to_le_bytes());
+        bytes.extend_from_slice(&entry.kind.to_le_bytes());
+        bytes.extend_from_slice(&(entry.count as u32).to_le_bytes());
+
+        if entry.data.len() <= 4 {
+            let mut value = entry.data.clone();
+            value.resize(4, 0);
+            bytes.extend_from_slice(&value);
+        } else {
+            bytes.extend_from_slice(&((overflow_offset + overflow.len()) as u32).to_le_bytes());
+            overflow.extend_from_slice(&entry.data);
+            if overflow.len() % 2 == 1 {
+                overflow.push(0);
+            }
+        }
+    }
+
+    // There is no further directory
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&overflow);
+
+    bytes
+}
@@ -0,0 +1,240 @@
+use geo::Point;
+use svg::{node::element::Rectangle, Document};
+use wasm_bindgen::prelude::*;
+
+mod ascii_grid;
+mod geotiff;
+mod png;
+
+/// Largest number of cells a raster may consist of, each cell requires a search through all nearby locations
+const MAXIMUM_RASTER_CELLS: usize = 65_536;
+
+/// Value written for cells from which no location is reachable, in formats that can not represent NaN
+const NO_DATA: f32 = -9999.0;
+
+/// Number of distinct colors the altitudes are divided into, yields bands that read like contour lines
+const COLOR_STEPS: usize = 16;
+
+/// Colors of the safe, risky and unsafe classifications, which low, medium and high altitudes are shown in
+const COLOR_RAMP: [[u8; 3]; 3] = [[0x38, 0x8E, 0x3C], [0xFF, 0xC1, 0x07], [0xE6, 0x4A, 0x19]];
+
+/// Regular grid of the minimum altitudes (in meters) that are required to reach a landing location
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct AltitudeRaster {
+    /// Longitude and latitude of the north-western corner of the grid
+    west: f64,
+    north: f64,
+    /// Size of each cell (in degrees)
+    dx: f64,
+    dy: f64,
+    columns: usize,
+    rows: usize,
+    /// Row-major altitudes beginning in the north-west, NaN where nothing is reachable below the ceiling
+    values: Vec<f32>,
+    /// Highest altitude that has been considered, which marks the upper end of the color ramp
+    ceiling: f64,
+}
+
+impl AltitudeRaster {
+    /// Covers the bounding box with cells of the given size (in degrees) and evaluates the altitude
+    /// at the center of each cell
+    pub fn sample<F>(
+        (west, south, east, north): (f64, f64, f64, f64),
+        (dx, dy): (f64, f64),
+        ceiling: f64,
+        mut altitude: F,
+    ) -> Result<Self, String>
+    where
+        F: FnMut(Point<f64>) -> Option<f64>,
+    {
+        if !(east > west && north > south && dx > 0.0 && dy > 0.0) {
+            return Err(String::from("Raster has no extent"));
+        }
+
+        let columns = ((east - west) / dx).ceil() as usize;
+        let rows = ((north - south) / dy).ceil() as usize;
+
+        if columns.saturating_mul(rows) > MAXIMUM_RASTER_CELLS {
+            return Err(format!(
+                "Raster of {}x{} cells is too large, use a coarser resolution",
+                columns, rows
+            ));
+        }
+
+        let values = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                let center = Point::new(
+                    west + (column as f64 + 0.5) * dx,
+                    north - (row as f64 + 0.5) * dy,
+                );
+
+                altitude(center).map_or(f32::NAN, |altitude| altitude as f32)
+            })
+            .collect();
+
+        Ok(Self {
+            west,
+            north,
+            dx,
+            dy,
+            columns,
+            rows,
+            values,
+            ceiling,
+        })
+    }
+
+    /// Color of a cell, altitudes are divided into bands ranging from green at the ground to red at the ceiling.
+    /// Cells from which nothing is reachable below the ceiling have no color and are left transparent.
+    fn color(&self, value: f32) -> Option<[u8; 3]> {
+        if value.is_nan() {
+            return None;
+        }
+
+        let fraction = (value as f64 / self.ceiling).clamp(0.0, 1.0);
+
+        let step = (fraction * COLOR_STEPS as f64)
+            .floor()
+            .min((COLOR_STEPS - 1) as f64);
+        let position = step / (COLOR_STEPS - 1) as f64 * (COLOR_RAMP.len() - 1) as f64;
+        let (lower, upper) = (
+            position.floor() as usize,
+            (position.ceil() as usize).min(COLOR_RAMP.len() - 1),
+        );
+        let fraction = position.fract();
+
+        let mut color = [0; 3];
+        for (channel, value) in color.iter_mut().enumerate() {
+            let (lower, upper) = (COLOR_RAMP[lower][channel], COLOR_RAMP[upper][channel]);
+            *value = (lower as f64 + (upper as f64 - lower as f64) * fraction).round() as u8;
+        }
+
+        Some(color)
+    }
+}
+
+#[wasm_bindgen]
+impl AltitudeRaster {
+    #[wasm_bindgen(getter)]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Area covered by the cells as [west, south, east, north], which may exceed the requested bounds by part of a cell
+    #[wasm_bindgen(getter)]
+    pub fn bounds(&self) -> Box<[f64]> {
+        Box::new([
+            self.west,
+            self.north - self.rows as f64 * self.dy,
+            self.west + self.columns as f64 * self.dx,
+            self.north,
+        ])
+    }
+
+    /// Row-major altitudes beginning in the north-west, NaN where nothing is reachable below the ceiling
+    #[wasm_bindgen(getter)]
+    pub fn altitudes(&self) -> Box<[f32]> {
+        self.values.clone().into_boxed_slice()
+    }
+
+    #[wasm_bindgen(js_name = toASCIIGrid)]
+    pub fn to_ascii_grid(&self) -> String {
+        ascii_grid::write_ascii_grid(self)
+    }
+
+    /// Single band of 32-bit floats in geographic coordinates (EPSG:4326)
+    #[wasm_bindgen(js_name = toGeoTIFF)]
+    pub fn to_geotiff(&self) -> Vec<u8> {
+        geotiff::write_geotiff(self)
+    }
+
+    /// Image with one pixel per cell, to be stretched across the bounds as an overlay. Cells from which nothing
+    /// is reachable are transparent.
+    #[wasm_bindgen(js_name = toPNG)]
+    pub fn to_png(&self) -> Vec<u8> {
+        let pixels = self
+            .values
+            .iter()
+            .flat_map(|value| match self.color(*value) {
+                Some([red, green, blue]) => [red, green, blue, 255],
+                None => [0, 0, 0, 0],
+            })
+            .collect::<Vec<_>>();
+
+        png::write_png(self.columns, self.rows, &pixels)
+    }
+
+    /// Image with one unit per cell, to be stretched across the bounds as an overlay
+    #[wasm_bindgen(js_name = toSVG)]
+    pub fn to_svg(&self) -> String {
+        let mut document = Document::new()
+            .set("viewBox", (0, 0, self.columns, self.rows))
+            .set("preserveAspectRatio", "none")
+            .set("shape-rendering", "crispEdges");
+
+        // Neighbouring cells of the same color are merged into a single rectangle
+        for (row, values) in self.values.chunks(self.columns).enumerate() {
+            let mut column = 0;
+            while column < values.len() {
+                let color = self.color(values[column]);
+                let width = values[column..]
+                    .iter()
+                    .take_while(|value| self.color(**value) == color)
+                    .count();
+
+                // Cells without a color are left out, which keeps them transparent
+                if let Some([red, green, blue]) = color {
+                    document = document.add(
+                        Rectangle::new()
+                            .set("x", column)
+                            .set("y", row)
+                            .set("width", width)
+                            .set("height", 1)
+                            .set("fill", format!("#{:02X}{:02X}{:02X}", red, green, blue)),
+                    );
+                }
+
+                column += width;
+            }
+        }
+
+        document.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raster of a single row with a reachable and an unreachable cell
+    fn raster() -> AltitudeRaster {
+        AltitudeRaster {
+            west: 10.0,
+            north: 53.5,
+            dx: 0.01,
+            dy: 0.01,
+            columns: 2,
+            rows: 1,
+            values: vec![0.0, f32::NAN],
+            ceiling: 1000.0,
+        }
+    }
+
+    #[test]
+    fn unreachable_cells_are_transparent() {
+        let raster = raster();
+        assert_eq!(raster.color(0.0), Some([0x38, 0x8E, 0x3C]));
+        assert_eq!(raster.color(f32::NAN), None);
+
+        let svg = raster.to_svg();
+        assert_eq!(svg.matches("<rect").count(), 1);
+        assert!(svg.contains("fill=\"#388E3C\""));
+    }
+}
@@ -0,0 +1,84 @@
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Largest amount of data a single stored deflate block may hold
+const MAXIMUM_BLOCK_SIZE: usize = 65_535;
+
+/// Writes an 8-bit RGBA image. The pixel data is wrapped in stored (uncompressed) deflate blocks,
+/// which every decoder supports and which saves carrying an actual compressor around.
+pub(super) fn write_png(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // Bit depth, color type (RGBA), compression, filter and interlace method
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    // Each row is preceded by its filter type, which is always none
+    let mut data = Vec::with_capacity(pixels.len() + height);
+    for row in pixels.chunks(width * 4) {
+        data.push(0);
+        data.extend_from_slice(row);
+    }
+
+    let mut bytes = SIGNATURE.to_vec();
+    write_chunk(&mut bytes, b"IHDR", &header);
+    write_chunk(&mut bytes, b"IDAT", &zlib_stored(&data));
+    write_chunk(&mut bytes, b"IEND", &[]);
+
+    bytes
+}
+
+fn write_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = bytes.len();
+    bytes.extend_from_slice(kind);
+    bytes.extend_from_slice(data);
+
+    let checksum = crc32(&bytes[start..]);
+    bytes.extend_from_slice(&checksum.to_be_bytes());
+}
+
+/// Zlib stream consisting of stored deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window and no preset dictionary, chosen so that the header is a multiple of 31
+    let mut stream = vec![0x78, 0x01];
+
+    let blocks = data.chunks(MAXIMUM_BLOCK_SIZE).collect::<Vec<_>>();
+    if blocks.is_empty() {
+        stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+
+    for (index, block) in blocks.iter().enumerate() {
+        let last = index + 1 == blocks.len();
+        let length = block.len() as u16;
+
+        stream.push(last as u8);
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65_521;
+        (a, (b + a) % 65_521)
+    });
+
+    (b << 16) | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
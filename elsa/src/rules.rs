@@ -0,0 +1,802 @@
+use crate::{HumanPresenceCategory, Preferences, RiskClassification, SurfaceType, UsageType};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Ruleset which reproduces the built-in risk assessment
+const DEFAULT_RULES: &str = include_str!("../../static/data/rules.yml");
+
+/// Aspect of a location which is classified by rules
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskFactor {
+    Surface,
+    Headroom,
+    Humans,
+    Obstacles,
+    Crops,
+    Staleness,
+}
+
+impl RiskFactor {
+    /// Contribution of the factor to the risk score relative to the others
+    fn weight(&self) -> f64 {
+        match self {
+            RiskFactor::Surface => 3.0,
+            RiskFactor::Headroom => 3.0,
+            RiskFactor::Humans => 2.0,
+            RiskFactor::Obstacles => 2.0,
+            RiskFactor::Crops => 2.0,
+            RiskFactor::Staleness => 1.0,
+        }
+    }
+}
+
+/// Extent to which obstacles on the approach shorten the usable runway
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum Obstruction {
+    None,
+    Partial,
+    Complete,
+}
+
+/// Value within a rule which is either given literally or refers to a key of the `Preferences`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum RuleValue<T> {
+    Literal(T),
+    Preference(String),
+}
+
+/// Conditions which all have to apply for a rule to match, omitted conditions always apply
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RuleConditions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface: Option<Vec<SurfaceType>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Vec<UsageType>>,
+    /// Human presence expected at the time of the assessment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence: Option<Vec<HumanPresenceCategory>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obstruction: Option<Vec<Obstruction>>,
+    /// Overall ditching assessment, water locations without ditching characteristics count as unsafe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ditching: Option<Vec<RiskClassification>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standing_crop: Option<bool>,
+    /// Applies when the landing headroom (fraction of the required distance) is below the value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headroom_below: Option<RuleValue<f64>>,
+    /// Applies when the survey is older than the value (in days) or its date is unknown.
+    /// Referring to a preference that is not set disables the rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub survey_age_above: Option<RuleValue<f64>>,
+    /// Identifiers of the aircraft to which the rule applies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aircraft: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aircraft_category: Option<Vec<String>>,
+}
+
+/// Classification applied to a risk factor when all conditions apply
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RiskRule {
+    pub name: String,
+    /// Machine-readable identifier of the rule (e.g. `HEADROOM_BELOW_RISKY`) reported when it applies
+    pub code: String,
+    pub factor: RiskFactor,
+    pub classification: RuleValue<RiskClassification>,
+    #[serde(default)]
+    pub when: RuleConditions,
+}
+
+/// Explanation why a risk factor has been raised, as reported by a matching rule
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskReason {
+    pub code: String,
+    pub factor: RiskFactor,
+    pub classification: RiskClassification,
+    /// Value of the location which has been compared, if the rule has a numeric condition and the value is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// Threshold the value has been compared against
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+}
+
+/// Ordered list of rules used to assess the risk of locations
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(transparent)]
+pub struct RiskRules(Vec<RiskRule>);
+
+/// Everything known about a location, the aircraft and the point in time which rules may refer to
+pub struct RiskFacts<'a> {
+    pub surface: SurfaceType,
+    pub usage: UsageType,
+    pub presence: HumanPresenceCategory,
+    pub obstruction: Obstruction,
+    /// `None` if the location is not on water
+    pub ditching: Option<RiskClassification>,
+    pub standing_crop: bool,
+    pub headroom: f64,
+    /// `None` if the survey date is unknown
+    pub survey_age: Option<i64>,
+    pub aircraft: &'a str,
+    pub aircraft_category: Option<&'a str>,
+}
+
+impl Default for RiskRules {
+    fn default() -> Self {
+        Self::from_yaml(DEFAULT_RULES).expect("default risk rules are malformed")
+    }
+}
+
+impl RiskRules {
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        serde_yaml::from_str(yaml).map_err(|e| e.to_string())
+    }
+
+    pub fn to_yaml(&self) -> Result<String, String> {
+        serde_yaml::to_string(self).map_err(|e| e.to_string())
+    }
+
+    pub fn rules(&self) -> &[RiskRule] {
+        &self.0
+    }
+
+    /// Reasons of all matching rules which classify a factor as anything but safe
+    pub fn reasons(&self, preferences: &Preferences, facts: &RiskFacts) -> Vec<RiskReason> {
+        // Rules refer to preferences by the names they carry in JS
+        let preferences = serde_json::to_value(preferences).unwrap_or(Value::Null);
+
+        self.0
+            .iter()
+            .filter_map(|rule| {
+                if !rule.when.apply(&preferences, facts)? {
+                    return None;
+                }
+
+                let classification = rule.classification.resolve(&preferences)?;
+                let (value, threshold) = rule.when.measurement(&preferences, facts);
+
+                Some(RiskReason {
+                    code: rule.code.clone(),
+                    factor: rule.factor,
+                    classification,
+                    value,
+                    threshold,
+                })
+            })
+            .filter(|reason| reason.classification != RiskClassification::Safe)
+            .collect()
+    }
+}
+
+impl RiskReason {
+    /// How far the reason reaches into the score range of its classification, from 0 to 1.
+    /// Numeric reasons grow with the relative distance to their threshold, an unknown value is the worst case.
+    fn severity(&self) -> f64 {
+        match (self.value, self.threshold) {
+            (Some(value), Some(threshold)) => {
+                ((value - threshold).abs() / threshold.abs().max(1.0)).min(1.0)
+            }
+            (None, Some(_)) => 1.0,
+            _ => 0.5,
+        }
+    }
+}
+
+/// Risk score between 0 and 100 for the classified factors and the reasons that led to them.
+///
+/// Each factor is placed within the score range of its classification according to its reasons. The weighted
+/// average of all factors then determines the position within the range of the overall classification,
+/// which keeps the score consistent with the classification while allowing locations of the same class to be ranked.
+pub fn risk_score(factors: &[(RiskFactor, RiskClassification)], reasons: &[RiskReason]) -> f64 {
+    let overall = factors
+        .iter()
+        .fold(RiskClassification::Safe, |risk, (_, classification)| {
+            risk + *classification
+        });
+
+    let (weighted, weights) = factors.iter().fold(
+        (0.0, 0.0),
+        |(weighted, weights), (factor, classification)| {
+            let (lower, upper) = classification.score_range();
+            let severity = reasons
+                .iter()
+                .filter(|reason| reason.factor == *factor)
+                .map(RiskReason::severity)
+                .fold(0.0, f64::max);
+            let score = lower + (upper - lower) * severity;
+
+            (
+                weighted + score / 100.0 * factor.weight(),
+                weights + factor.weight(),
+            )
+        },
+    );
+
+    let (lower, upper) = overall.score_range();
+    let position = if weights > 0.0 {
+        weighted / weights
+    } else {
+        0.0
+    };
+
+    // The average only reaches 1 if every factor is unsafe, thus the score never leaves the range of the overall classification
+    lower + (upper - lower) * position
+}
+
+impl RuleConditions {
+    /// Whether all conditions apply, `None` if a condition refers to an unknown preference
+    fn apply(&self, preferences: &Value, facts: &RiskFacts) -> Option<bool> {
+        let headroom = match &self.headroom_below {
+            Some(threshold) => facts.headroom < threshold.resolve(preferences)?,
+            None => true,
+        };
+
+        // Locations without a survey date are considered outdated, unless the rule is disabled
+        let survey_age = match &self.survey_age_above {
+            Some(threshold) => {
+                let threshold = threshold.resolve(preferences)?;
+
+                match facts.survey_age {
+                    Some(age) => age as f64 > threshold,
+                    None => true,
+                }
+            }
+            None => true,
+        };
+
+        let standing_crop = match self.standing_crop {
+            Some(standing_crop) => standing_crop == facts.standing_crop,
+            None => true,
+        };
+
+        Some(
+            contains(&self.surface, Some(facts.surface))
+                && contains(&self.usage, Some(facts.usage))
+                && contains(&self.presence, Some(facts.presence))
+                && contains(&self.obstruction, Some(facts.obstruction))
+                && contains(&self.ditching, facts.ditching)
+                && contains(&self.aircraft, Some(facts.aircraft.to_string()))
+                && contains(
+                    &self.aircraft_category,
+                    facts.aircraft_category.map(String::from),
+                )
+                && standing_crop
+                && headroom
+                && survey_age,
+        )
+    }
+
+    /// Value and threshold of the numeric condition, if any
+    fn measurement(&self, preferences: &Value, facts: &RiskFacts) -> (Option<f64>, Option<f64>) {
+        if let Some(threshold) = &self.headroom_below {
+            (Some(facts.headroom), threshold.resolve(preferences))
+        } else if let Some(threshold) = &self.survey_age_above {
+            (
+                facts.survey_age.map(|age| age as f64),
+                threshold.resolve(preferences),
+            )
+        } else {
+            (None, None)
+        }
+    }
+}
+
+/// Whether the value is part of the list, conditions without a list always apply while missing values never do
+fn contains<T: PartialEq>(list: &Option<Vec<T>>, value: Option<T>) -> bool {
+    match (list, value) {
+        (Some(list), Some(value)) => list.contains(&value),
+        (Some(_), None) => false,
+        (None, _) => true,
+    }
+}
+
+impl<T: Clone + for<'de> Deserialize<'de>> RuleValue<T> {
+    /// Literal value or the value of the referenced preference, `None` if there is no such preference or it is not set
+    fn resolve(&self, preferences: &Value) -> Option<T> {
+        match self {
+            RuleValue::Literal(value) => Some(value.clone()),
+            // Preferences which are not set disable the rules referring to them
+            RuleValue::Preference(key) if preferences.get(key) == Some(&Value::Null) => None,
+            RuleValue::Preference(key) => {
+                let value = preferences
+                    .get(key)
+                    .and_then(|value| serde_json::from_value(value.clone()).ok());
+
+                if value.is_none() {
+                    log::warn!("Risk rule refers to unknown preference '{}'", key);
+                }
+
+                value
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Preferences with the given survey ages (in days), `null` leaves them unset
+    fn preferences(risky_survey_age: &str, unsafe_survey_age: &str) -> Preferences {
+        serde_json::from_str(&format!(
+            r#"{{
+                "bank": 0.78,
+                "epsilon": 0.1,
+                "riskyLandingHeadroom": -0.05,
+                "unsafeLandingHeadroom": -0.15,
+                "eventLocationClassification": "risky",
+                "denselyCrowdedClassification": "unsafe",
+                "riskySurveyAge": {},
+                "unsafeSurveyAge": {}
+            }}"#,
+            risky_survey_age, unsafe_survey_age
+        ))
+        .unwrap()
+    }
+
+    /// Facts of a safe grass field surveyed the given number of days ago
+    fn facts(survey_age: Option<i64>) -> RiskFacts<'static> {
+        RiskFacts {
+            surface: SurfaceType::Gras,
+            usage: UsageType::Agricultural,
+            presence: HumanPresenceCategory::Unlikely,
+            obstruction: Obstruction::None,
+            ditching: None,
+            standing_crop: false,
+            headroom: 0.5,
+            survey_age,
+            aircraft: "C172",
+            aircraft_category: None,
+        }
+    }
+
+    /// Codes of all reasons the default rules give
+    fn codes(preferences: &Preferences, facts: &RiskFacts) -> Vec<String> {
+        RiskRules::default()
+            .reasons(preferences, facts)
+            .into_iter()
+            .map(|reason| reason.code)
+            .collect()
+    }
+
+    #[test]
+    fn survey_age_rules() {
+        let unset = preferences("null", "null");
+        let set = preferences("365", "730");
+
+        // Unset preferences disable the rules, even if the survey date is unknown
+        assert!(codes(&unset, &facts(None)).is_empty());
+        assert!(codes(&unset, &facts(Some(1000))).is_empty());
+
+        assert!(codes(&set, &facts(Some(365))).is_empty());
+        assert_eq!(
+            codes(&set, &facts(Some(366))),
+            vec!["SURVEY_AGE_ABOVE_RISKY"]
+        );
+        assert_eq!(
+            codes(&set, &facts(Some(731))),
+            vec!["SURVEY_AGE_ABOVE_RISKY", "SURVEY_AGE_ABOVE_UNSAFE"]
+        );
+        assert_eq!(
+            codes(&set, &facts(None)),
+            vec!["SURVEY_AGE_ABOVE_RISKY", "SURVEY_AGE_ABOVE_UNSAFE"]
+        );
+
+        // Only the preference which is set applies
+        assert_eq!(
+            codes(&preferences("null", "730"), &facts(None)),
+            vec!["SURVEY_AGE_ABOVE_UNSAFE"]
+        );
+    }
+
+    #[test]
+    fn headroom_below_reasons() {
+        let preferences = preferences("null", "null");
+        let reasons = |headroom| {
+            let facts = RiskFacts {
+                headroom,
+                ..facts(Some(10))
+            };
+
+            serde_json::to_value(RiskRules::default().reasons(&preferences, &facts)).unwrap()
+        };
+
+        assert_eq!(
+            reasons(-0.2),
+            serde_json::json!([
+                {
+                    "code": "HEADROOM_BELOW_UNSAFE",
+                    "factor": "headroom",
+                    "classification": "unsafe",
+                    "value": -0.2,
+                    "threshold": -0.15
+                },
+                {
+                    "code": "HEADROOM_BELOW_RISKY",
+                    "factor": "headroom",
+                    "classification": "risky",
+                    "value": -0.2,
+                    "threshold": -0.05
+                }
+            ])
+        );
+        assert_eq!(
+            reasons(-0.1),
+            serde_json::json!([{
+                "code": "HEADROOM_BELOW_RISKY",
+                "factor": "headroom",
+                "classification": "risky",
+                "value": -0.1,
+                "threshold": -0.05
+            }])
+        );
+        // Thresholds themselves are not below
+        assert_eq!(reasons(-0.05), serde_json::json!([]));
+    }
+
+    const FACTORS: [RiskFactor; 6] = [
+        RiskFactor::Surface,
+        RiskFactor::Headroom,
+        RiskFactor::Humans,
+        RiskFactor::Obstacles,
+        RiskFactor::Crops,
+        RiskFactor::Staleness,
+    ];
+
+    /// Reason whose value lies beyond its threshold so far that it has the given severity
+    fn reason(factor: RiskFactor, classification: RiskClassification, severity: f64) -> RiskReason {
+        RiskReason {
+            code: String::from("TEST"),
+            factor,
+            classification,
+            value: Some(-severity),
+            threshold: Some(0.0),
+        }
+    }
+
+    /// Score for the given classification of each factor, all reasons share the same severity
+    fn score(classifications: &[RiskClassification], severity: f64) -> f64 {
+        let factors = FACTORS
+            .iter()
+            .cloned()
+            .zip(classifications.iter().cloned())
+            .collect::<Vec<_>>();
+        let reasons = factors
+            .iter()
+            .filter(|(_, classification)| *classification != RiskClassification::Safe)
+            .map(|(factor, classification)| reason(*factor, *classification, severity))
+            .collect::<Vec<_>>();
+
+        risk_score(&factors, &reasons)
+    }
+
+    #[test]
+    fn risk_score_stays_within_classification() {
+        use RiskClassification::*;
+
+        // Every combination of classifications for the six factors
+        let combinations = (0..3usize.pow(6)).map(|index| {
+            (0..6)
+                .map(|factor| RiskClassification::from((index / 3usize.pow(factor) % 3) as u8))
+                .collect::<Vec<_>>()
+        });
+
+        for classifications in combinations {
+            let overall = classifications.iter().fold(Safe, |risk, c| risk + *c);
+            let (lower, upper) = overall.score_range();
+
+            for severity in [0.0, 0.25, 0.5, 1.0].iter() {
+                let score = score(&classifications, *severity);
+
+                assert!(lower <= score && score <= upper);
+                assert_eq!(
+                    RiskClassification::from_score(score),
+                    overall,
+                    "{:?} with severity {} scored {}",
+                    classifications,
+                    severity,
+                    score
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn risk_score_is_monotonic_within_classification() {
+        use RiskClassification::*;
+
+        for overall in [Risky, Unsafe].iter() {
+            // Reasons further beyond their threshold never lower the score
+            let scores = (0..=20)
+                .map(|step| {
+                    score(
+                        &[*overall, Safe, Safe, Safe, Safe, Safe],
+                        step as f64 * 0.05,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            assert!(scores.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(scores[0] < scores[20]);
+
+            // Every additional factor which is raised within the overall classification raises the score
+            let scores = (0..6)
+                .map(|raised| {
+                    let classifications = (0..6)
+                        .map(|factor| if factor <= raised { *overall } else { Safe })
+                        .collect::<Vec<_>>();
+
+                    score(&classifications, 0.5)
+                })
+                .collect::<Vec<_>>();
+
+            assert!(scores.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        // Risky factors still count when another one is unsafe
+        assert!(
+            score(&[Unsafe, Risky, Safe, Safe, Safe, Safe], 0.5)
+                > score(&[Unsafe, Safe, Safe, Safe, Safe, Safe], 0.5)
+        );
+    }
+
+    /// Classification of every factor as assessed before risks were described by rules
+    fn built_in(preferences: &Preferences, facts: &RiskFacts) -> [RiskClassification; 6] {
+        use RiskClassification::*;
+
+        let surface = match facts.surface {
+            SurfaceType::Water => facts.ditching.unwrap_or(Unsafe),
+            _ => Safe,
+        };
+        let headroom = match facts.headroom {
+            headroom if headroom < preferences.unsafe_landing_headroom => Unsafe,
+            headroom if headroom < preferences.risky_landing_headroom => Risky,
+            _ => Safe,
+        };
+        let humans = match facts.presence {
+            HumanPresenceCategory::EventOnly => preferences.event_location_classification,
+            HumanPresenceCategory::Dense => preferences.densely_crowded_classification,
+            _ => Safe,
+        };
+        let obstacles = match facts.obstruction {
+            Obstruction::Complete => Unsafe,
+            Obstruction::Partial => Risky,
+            Obstruction::None => Safe,
+        };
+        let crops = if facts.standing_crop { Unsafe } else { Safe };
+        let exceeds = |threshold: Option<u32>| match (threshold, facts.survey_age) {
+            (Some(threshold), Some(age)) => age > threshold as i64,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        let staleness = if exceeds(preferences.unsafe_survey_age) {
+            Unsafe
+        } else if exceeds(preferences.risky_survey_age) {
+            Risky
+        } else {
+            Safe
+        };
+
+        [surface, headroom, humans, obstacles, crops, staleness]
+    }
+
+    /// Classification of every factor as the most severe reason the rules give
+    fn classify(
+        rules: &RiskRules,
+        preferences: &Preferences,
+        facts: &RiskFacts,
+    ) -> [RiskClassification; 6] {
+        let reasons = rules.reasons(preferences, facts);
+        let factor = |factor| {
+            reasons
+                .iter()
+                .filter(|reason| reason.factor == factor)
+                .fold(RiskClassification::Safe, |risk, reason| {
+                    risk + reason.classification
+                })
+        };
+
+        [
+            factor(RiskFactor::Surface),
+            factor(RiskFactor::Headroom),
+            factor(RiskFactor::Humans),
+            factor(RiskFactor::Obstacles),
+            factor(RiskFactor::Crops),
+            factor(RiskFactor::Staleness),
+        ]
+    }
+
+    #[test]
+    fn default_rules_reproduce_built_in_classification() {
+        use HumanPresenceCategory::*;
+        use RiskClassification::*;
+
+        let rules = RiskRules::default();
+        let surfaces = [
+            (SurfaceType::Gras, None),
+            (SurfaceType::Asphalt, None),
+            (SurfaceType::Water, Some(Risky)),
+            (SurfaceType::Water, Some(Unsafe)),
+        ];
+        let mut preferences_variants = Vec::new();
+        for (risky_survey_age, unsafe_survey_age) in
+            [("null", "null"), ("365", "730"), ("null", "730")].iter()
+        {
+            for (event, dense) in [(Risky, Unsafe), (Unsafe, Risky), (Safe, Safe)].iter() {
+                preferences_variants.push(Preferences {
+                    event_location_classification: *event,
+                    densely_crowded_classification: *dense,
+                    ..preferences(risky_survey_age, unsafe_survey_age)
+                });
+            }
+        }
+
+        // Every combination of the facts the default rules refer to
+        let mut cases = vec![facts(None)];
+        cases = cases
+            .iter()
+            .flat_map(|base| {
+                surfaces.iter().map(move |&(surface, ditching)| RiskFacts {
+                    surface,
+                    ditching,
+                    ..*base
+                })
+            })
+            .collect();
+        cases = cases
+            .iter()
+            .flat_map(|base| {
+                [Dense, Sparse, EventOnly, Unlikely]
+                    .iter()
+                    .map(move |&presence| RiskFacts { presence, ..*base })
+            })
+            .collect();
+        cases = cases
+            .iter()
+            .flat_map(|base| {
+                [
+                    Obstruction::None,
+                    Obstruction::Partial,
+                    Obstruction::Complete,
+                ]
+                .iter()
+                .map(move |&obstruction| RiskFacts {
+                    obstruction,
+                    ..*base
+                })
+            })
+            .collect();
+        cases = cases
+            .iter()
+            .flat_map(|base| {
+                [false, true].iter().map(move |&standing_crop| RiskFacts {
+                    standing_crop,
+                    ..*base
+                })
+            })
+            .collect();
+        cases = cases
+            .iter()
+            .flat_map(|base| {
+                [-0.2, -0.15, -0.1, -0.05, 0.0, 0.3]
+                    .iter()
+                    .map(move |&headroom| RiskFacts { headroom, ..*base })
+            })
+            .collect();
+        cases = cases
+            .iter()
+            .flat_map(|base| {
+                [None, Some(10), Some(400), Some(800)]
+                    .iter()
+                    .map(move |&survey_age| RiskFacts {
+                        survey_age,
+                        ..*base
+                    })
+            })
+            .collect();
+
+        for preferences in preferences_variants.iter() {
+            for facts in cases.iter() {
+                assert_eq!(
+                    classify(&rules, preferences, facts),
+                    built_in(preferences, facts)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_preferences_disable_rules() {
+        let rules = RiskRules::from_yaml(
+            "
+            - name: Threshold of a typo
+              code: HEADROOM_BELOW_TYPO
+              factor: headroom
+              classification: unsafe
+              when:
+                headroomBelow: unsafeLandingHeadrom
+            - name: Classification of a typo
+              code: HUMANS_TYPO
+              factor: humans
+              classification: eventLocationClasification
+            - name: Always applies
+              code: STALENESS_ALWAYS
+              factor: staleness
+              classification: risky
+            ",
+        )
+        .unwrap();
+
+        let codes = rules
+            .reasons(&preferences("null", "null"), &facts(None))
+            .into_iter()
+            .map(|reason| reason.code)
+            .collect::<Vec<_>>();
+
+        assert_eq!(codes, vec!["STALENESS_ALWAYS"]);
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let rule = |when: &str| {
+            RiskRules::from_yaml(&format!(
+                "
+                - name: Landing distance is insufficient
+                  code: HEADROOM_BELOW_UNSAFE
+                  factor: headroom
+                  classification: unsafe
+                  {}
+                ",
+                when
+            ))
+        };
+
+        assert!(rule("when: { headroomBelow: 0.1 }").is_ok());
+        assert!(rule("when: { headroomBellow: 0.1 }").is_err());
+        assert!(rule("wehn: { headroomBelow: 0.1 }").is_err());
+    }
+
+    #[test]
+    fn rule_values_are_literals_or_preferences() {
+        let conditions: RuleConditions =
+            serde_yaml::from_str("{ headroomBelow: -0.1, surveyAgeAbove: riskySurveyAge }")
+                .unwrap();
+
+        assert!(matches!(
+            conditions.headroom_below,
+            Some(RuleValue::Literal(value)) if value == -0.1
+        ));
+        assert!(matches!(
+            conditions.survey_age_above,
+            Some(RuleValue::Preference(ref key)) if key == "riskySurveyAge"
+        ));
+
+        // Numbers given as strings are no numbers
+        let conditions: RuleConditions = serde_yaml::from_str("{ headroomBelow: '-0.1' }").unwrap();
+
+        assert!(matches!(
+            conditions.headroom_below,
+            Some(RuleValue::Preference(ref key)) if key == "-0.1"
+        ));
+
+        let classification =
+            |yaml| serde_yaml::from_str::<RuleValue<RiskClassification>>(yaml).unwrap();
+
+        assert!(matches!(
+            classification("unsafe"),
+            RuleValue::Literal(RiskClassification::Unsafe)
+        ));
+        assert!(matches!(
+            classification("eventLocationClassification"),
+            RuleValue::Preference(ref key) if key == "eventLocationClassification"
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use super::{Crs, ElevationGrid, MAXIMUM_GRID_CELLS};
+use std::collections::HashMap;
+
+/// Keys that may occur in the header of an ESRI ASCII grid
+const HEADER_KEYS: [&str; 10] = [
+    "ncols",
+    "nrows",
+    "xllcorner",
+    "yllcorner",
+    "xllcenter",
+    "yllcenter",
+    "cellsize",
+    "dx",
+    "dy",
+    "nodata_value",
+];
+
+/// Parses an ESRI ASCII grid, which consists of a header followed by rows of values beginning in the north
+pub(super) fn parse_ascii_grid(source: &str, crs: Crs) -> Result<ElevationGrid, String> {
+    let mut tokens = source.split_whitespace().peekable();
+    let mut header = HashMap::new();
+
+    while let Some(key) = tokens.peek().map(|token| token.to_lowercase()) {
+        if !HEADER_KEYS.contains(&key.as_str()) {
+            break;
+        }
+
+        tokens.next();
+        let value = tokens
+            .next()
+            .ok_or_else(|| format!("Missing value of header {}", key))?
+            .parse::<f64>()
+            .map_err(|e| format!("Header {}: {}", key, e))?;
+
+        if !value.is_finite() {
+            return Err(format!("Header {}: {} is not a finite number", key, value));
+        }
+
+        header.insert(key, value);
+    }
+
+    let get = |key: &str| header.get(key).copied();
+    let require = |key: &str| get(key).ok_or_else(|| format!("Missing header {}", key));
+
+    let columns = require("ncols")? as usize;
+    let rows = require("nrows")? as usize;
+    let dx = get("cellsize")
+        .or_else(|| get("dx"))
+        .ok_or("Missing header cellsize")?;
+    let dy = get("cellsize")
+        .or_else(|| get("dy"))
+        .ok_or("Missing header cellsize")?;
+
+    if columns.saturating_mul(rows) > MAXIMUM_GRID_CELLS {
+        return Err(format!("Grid of {}x{} cells is too large", columns, rows));
+    }
+
+    // The lower left position may either refer to the corner or the center of the cell
+    let west = match (get("xllcenter"), get("xllcorner")) {
+        (Some(center), _) => center,
+        (None, Some(corner)) => corner + dx / 2.0,
+        _ => return Err(String::from("Missing header xllcorner")),
+    };
+    let south = match (get("yllcenter"), get("yllcorner")) {
+        (Some(center), _) => center,
+        (None, Some(corner)) => corner + dy / 2.0,
+        _ => return Err(String::from("Missing header yllcorner")),
+    };
+    let north = south + (rows as f64 - 1.0) * dy;
+
+    let no_data = get("nodata_value");
+    let values = tokens
+        .map(|token| {
+            let value = token
+                .parse::<f64>()
+                .map_err(|e| format!("Value {}: {}", token, e))?;
+
+            if !value.is_finite() {
+                return Err(format!("Value {} is not a finite number", token));
+            }
+
+            Ok(match no_data {
+                Some(no_data) if value == no_data => f32::NAN,
+                _ => value as f32,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    ElevationGrid::new(crs, (west, north), (dx, dy), (columns, rows), values)
+}
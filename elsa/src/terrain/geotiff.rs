@@ -0,0 +1,427 @@
+use super::{Crs, ElevationGrid, MAXIMUM_GRID_CELLS};
+use std::collections::HashMap;
+
+const IMAGE_WIDTH: u16 = 256;
+const IMAGE_LENGTH: u16 = 257;
+const BITS_PER_SAMPLE: u16 = 258;
+const COMPRESSION: u16 = 259;
+const STRIP_OFFSETS: u16 = 273;
+const SAMPLES_PER_PIXEL: u16 = 277;
+const ROWS_PER_STRIP: u16 = 278;
+const STRIP_BYTE_COUNTS: u16 = 279;
+const PLANAR_CONFIGURATION: u16 = 284;
+const PREDICTOR: u16 = 317;
+const TILE_WIDTH: u16 = 322;
+const TILE_LENGTH: u16 = 323;
+const TILE_OFFSETS: u16 = 324;
+const TILE_BYTE_COUNTS: u16 = 325;
+const SAMPLE_FORMAT: u16 = 339;
+const MODEL_PIXEL_SCALE: u16 = 33550;
+const MODEL_TIEPOINT: u16 = 33922;
+const MODEL_TRANSFORMATION: u16 = 34264;
+const GEO_KEY_DIRECTORY: u16 = 34735;
+const GDAL_NODATA: u16 = 42113;
+
+const MODEL_TYPE_GEO_KEY: u16 = 1024;
+const RASTER_TYPE_GEO_KEY: u16 = 1025;
+const PROJECTED_CS_TYPE_GEO_KEY: u16 = 3072;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+const USER_DEFINED: u16 = 32767;
+
+const LZW_CLEAR: usize = 256;
+const LZW_END_OF_INFORMATION: usize = 257;
+
+/// Value of a directory entry, ASCII entries are kept as text while everything else is converted into numbers
+enum Field {
+    Numbers(Vec<f64>),
+    Text(String),
+}
+
+/// Interpretation of the raw bits of a sample
+#[derive(Clone, Copy, PartialEq)]
+enum SampleFormat {
+    Unsigned,
+    Signed,
+    Float,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&self, offset: usize, length: usize) -> Result<&'a [u8], String> {
+        self.bytes
+            .get(offset..offset.saturating_add(length))
+            .ok_or_else(|| String::from("Unexpected end of file"))
+    }
+
+    /// Unsigned integer of the given number of bytes
+    fn unsigned(&self, offset: usize, size: usize) -> Result<u64, String> {
+        Ok(raw_sample(self.slice(offset, size)?, self.little_endian))
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, String> {
+        Ok(self.unsigned(offset, 2)? as u16)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, String> {
+        Ok(self.unsigned(offset, 4)? as u32)
+    }
+
+    fn number(&self, kind: u16, offset: usize) -> Result<f64, String> {
+        Ok(match kind {
+            1 | 7 => self.unsigned(offset, 1)? as f64,
+            6 => self.unsigned(offset, 1)? as u8 as i8 as f64,
+            3 => self.unsigned(offset, 2)? as f64,
+            8 => self.unsigned(offset, 2)? as u16 as i16 as f64,
+            4 => self.unsigned(offset, 4)? as f64,
+            9 => self.unsigned(offset, 4)? as u32 as i32 as f64,
+            5 => self.unsigned(offset, 4)? as f64 / self.unsigned(offset + 4, 4)? as f64,
+            10 => {
+                self.unsigned(offset, 4)? as u32 as i32 as f64
+                    / self.unsigned(offset + 4, 4)? as u32 as i32 as f64
+            }
+            11 => f32::from_bits(self.unsigned(offset, 4)? as u32) as f64,
+            12 => f64::from_bits(self.unsigned(offset, 8)?),
+            _ => return Err(format!("Unsupported field type {}", kind)),
+        })
+    }
+
+    /// Entries of the image file directory at the given offset
+    fn directory(&self, offset: usize) -> Result<HashMap<u16, Field>, String> {
+        let count = self.u16(offset)? as usize;
+        let mut fields = HashMap::new();
+
+        for index in 0..count {
+            let entry = offset + 2 + index * 12;
+            let tag = self.u16(entry)?;
+            let kind = self.u16(entry + 2)?;
+            let count = self.u32(entry + 4)? as usize;
+
+            let size = match kind {
+                1 | 2 | 6 | 7 => 1,
+                3 | 8 => 2,
+                4 | 9 | 11 => 4,
+                5 | 10 | 12 => 8,
+                // Unknown types are allowed by the specification and have to be skipped
+                _ => continue,
+            };
+
+            // Values that fit into four bytes are stored within the entry itself
+            let offset = if size * count <= 4 {
+                entry + 8
+            } else {
+                self.u32(entry + 8)? as usize
+            };
+
+            let field = if kind == 2 {
+                let text = String::from_utf8_lossy(self.slice(offset, count)?);
+                Field::Text(text.trim_end_matches('\0').to_string())
+            } else {
+                Field::Numbers(
+                    (0..count)
+                        .map(|i| self.number(kind, offset + i * size))
+                        .collect::<Result<_, _>>()?,
+                )
+            };
+
+            fields.insert(tag, field);
+        }
+
+        Ok(fields)
+    }
+}
+
+/// Parses the first band of the first image within a GeoTIFF. Supports uncompressed, LZW and PackBits
+/// compressed strips or tiles of integer or floating point samples.
+pub(super) fn parse_geotiff(bytes: &[u8]) -> Result<ElevationGrid, String> {
+    let little_endian = match bytes.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Err(String::from("Not a TIFF file")),
+    };
+    let reader = Reader {
+        bytes,
+        little_endian,
+    };
+
+    match reader.u16(2)? {
+        42 => {}
+        43 => return Err(String::from("BigTIFF files are not supported")),
+        _ => return Err(String::from("Not a TIFF file")),
+    }
+
+    let fields = reader.directory(reader.u32(4)? as usize)?;
+    let numbers = |tag: u16| match fields.get(&tag) {
+        Some(Field::Numbers(numbers)) => Some(numbers.as_slice()),
+        _ => None,
+    };
+    let number = |tag: u16, default: Option<f64>| {
+        numbers(tag)
+            .and_then(|numbers| numbers.first().copied())
+            .or(default)
+            .ok_or_else(|| format!("Missing TIFF tag {}", tag))
+    };
+
+    let width = number(IMAGE_WIDTH, None)? as usize;
+    let height = number(IMAGE_LENGTH, None)? as usize;
+    let bits = number(BITS_PER_SAMPLE, Some(1.0))? as usize;
+    let samples_per_pixel = number(SAMPLES_PER_PIXEL, Some(1.0))? as usize;
+    let planar = number(PLANAR_CONFIGURATION, Some(1.0))? as usize == 2;
+    let compression = number(COMPRESSION, Some(1.0))? as u16;
+    let predictor = number(PREDICTOR, Some(1.0))? as u16;
+    let format = match number(SAMPLE_FORMAT, Some(1.0))? as u16 {
+        1 => SampleFormat::Unsigned,
+        2 => SampleFormat::Signed,
+        3 => SampleFormat::Float,
+        format => return Err(format!("Unsupported sample format {}", format)),
+    };
+
+    match (format, bits) {
+        (SampleFormat::Float, 32 | 64)
+        | (SampleFormat::Unsigned | SampleFormat::Signed, 8 | 16 | 32) => {}
+        _ => return Err(format!("Unsupported sample size of {} bits", bits)),
+    }
+
+    if samples_per_pixel == 0 {
+        return Err(String::from("TIFF pixels have no samples"));
+    }
+
+    if predictor != 1 && !(predictor == 2 && format != SampleFormat::Float) {
+        return Err(format!("Unsupported predictor {}", predictor));
+    }
+
+    if width.saturating_mul(height) > MAXIMUM_GRID_CELLS {
+        return Err(format!("Grid of {}x{} cells is too large", width, height));
+    }
+
+    // Strips are treated as tiles spanning the full width of the image
+    let (chunk_width, chunk_height, offsets, byte_counts) = match numbers(TILE_OFFSETS) {
+        Some(offsets) => (
+            number(TILE_WIDTH, None)? as usize,
+            number(TILE_LENGTH, None)? as usize,
+            offsets,
+            numbers(TILE_BYTE_COUNTS).ok_or("Missing TIFF tile byte counts")?,
+        ),
+        None => (
+            width,
+            number(ROWS_PER_STRIP, Some(height as f64))? as usize,
+            numbers(STRIP_OFFSETS).ok_or("Missing TIFF strip offsets")?,
+            numbers(STRIP_BYTE_COUNTS).ok_or("Missing TIFF strip byte counts")?,
+        ),
+    };
+
+    if chunk_width == 0 || chunk_height == 0 {
+        return Err(String::from("TIFF tiles have no extent"));
+    }
+
+    let no_data = match fields.get(&GDAL_NODATA) {
+        Some(Field::Text(text)) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+
+    // Only the first band is read, with separate planes it consists of the leading chunks
+    let stride = if planar { 1 } else { samples_per_pixel };
+    let bytes_per_sample = bits / 8;
+    let chunks_across = (width + chunk_width - 1) / chunk_width;
+    let chunks_down = (height + chunk_height - 1) / chunk_height;
+    let mut values = vec![f32::NAN; width * height];
+
+    for chunk in 0..chunks_across * chunks_down {
+        let (offset, length) = match (offsets.get(chunk), byte_counts.get(chunk)) {
+            (Some(offset), Some(length)) => (*offset as usize, *length as usize),
+            _ => return Err(String::from("Missing TIFF chunk")),
+        };
+
+        let data = reader.slice(offset, length)?;
+        let data = match compression {
+            1 => data.to_vec(),
+            5 => decode_lzw(data)?,
+            32773 => decode_packbits(data),
+            compression => return Err(format!("Unsupported compression {}", compression)),
+        };
+
+        let mut samples = data
+            .chunks_exact(bytes_per_sample)
+            .map(|sample| raw_sample(sample, little_endian))
+            .collect::<Vec<_>>();
+
+        // Horizontal differencing stores each sample as the difference to its left neighbour
+        if predictor == 2 {
+            let mask = (1u64 << bits) - 1;
+            for row in samples.chunks_mut(chunk_width * stride) {
+                for i in stride..row.len() {
+                    row[i] = row[i].wrapping_add(row[i - stride]) & mask;
+                }
+            }
+        }
+
+        let (left, top) = (
+            (chunk % chunks_across) * chunk_width,
+            (chunk / chunks_across) * chunk_height,
+        );
+        for y in 0..chunk_height.min(height - top) {
+            for x in 0..chunk_width.min(width - left) {
+                let raw = match samples.get((y * chunk_width + x) * stride) {
+                    Some(raw) => *raw,
+                    None => continue,
+                };
+
+                let value = match format {
+                    SampleFormat::Unsigned => raw as f64,
+                    SampleFormat::Signed => ((raw << (64 - bits)) as i64 >> (64 - bits)) as f64,
+                    SampleFormat::Float if bits == 32 => f32::from_bits(raw as u32) as f64,
+                    SampleFormat::Float => f64::from_bits(raw),
+                };
+
+                if Some(value) != no_data {
+                    values[(top + y) * width + left + x] = value as f32;
+                }
+            }
+        }
+    }
+
+    // Georeferencing maps the raster space (pixel corners) onto the model space
+    let ((tie_column, tie_row), (tie_x, tie_y), (dx, dy)) = match (
+        numbers(MODEL_TIEPOINT),
+        numbers(MODEL_PIXEL_SCALE),
+        numbers(MODEL_TRANSFORMATION),
+    ) {
+        (Some([i, j, _, x, y, ..]), Some([sx, sy, ..]), _) => ((*i, *j), (*x, *y), (*sx, *sy)),
+        (_, _, Some([sx, rx, _, x, ry, sy, _, y, ..])) if *rx == 0.0 && *ry == 0.0 => {
+            ((0.0, 0.0), (*x, *y), (*sx, -*sy))
+        }
+        _ => {
+            return Err(String::from(
+                "GeoTIFF is either not georeferenced or rotated",
+            ))
+        }
+    };
+
+    let geo_keys = geo_keys(numbers(GEO_KEY_DIRECTORY).unwrap_or(&[]));
+    let crs = match (
+        geo_keys.get(&PROJECTED_CS_TYPE_GEO_KEY),
+        geo_keys.get(&MODEL_TYPE_GEO_KEY),
+    ) {
+        (Some(code), _) if *code != USER_DEFINED => Crs::from_epsg(*code as u32)?,
+        (_, Some(&MODEL_TYPE_GEOGRAPHIC)) => Crs::Geographic,
+        _ => {
+            return Err(String::from(
+                "GeoTIFF does not declare a supported reference system",
+            ))
+        }
+    };
+
+    // Values either describe the area of a pixel or the point at its upper left corner
+    let center = match geo_keys.get(&RASTER_TYPE_GEO_KEY) {
+        Some(&RASTER_PIXEL_IS_POINT) => 0.0,
+        _ => 0.5,
+    };
+    let west = tie_x + (center - tie_column) * dx;
+    let north = tie_y - (center - tie_row) * dy;
+
+    ElevationGrid::new(crs, (west, north), (dx, dy), (width, height), values)
+}
+
+/// GeoKeys whose value is stored directly within the directory
+fn geo_keys(directory: &[f64]) -> HashMap<u16, u16> {
+    directory
+        .get(4..)
+        .unwrap_or(&[])
+        .chunks_exact(4)
+        .filter(|key| key[1] == 0.0)
+        .map(|key| (key[0] as u16, key[3] as u16))
+        .collect()
+}
+
+/// Bit pattern of an unsigned integer with the size of the given bytes
+fn raw_sample(bytes: &[u8], little_endian: bool) -> u64 {
+    let fold = |value: u64, byte: &u8| (value << 8) | *byte as u64;
+
+    if little_endian {
+        bytes.iter().rev().fold(0, fold)
+    } else {
+        bytes.iter().fold(0, fold)
+    }
+}
+
+/// Decodes LZW compressed data as specified by TIFF 6.0, with codes stored most significant bit first
+fn decode_lzw(data: &[u8]) -> Result<Vec<u8>, String> {
+    let initial_table = || {
+        (0..=LZW_END_OF_INFORMATION)
+            .map(|byte| vec![byte as u8])
+            .collect::<Vec<_>>()
+    };
+    let mut table = initial_table();
+    let mut width = 9;
+    let mut position = 0;
+    let mut previous: Option<Vec<u8>> = None;
+    let mut output = Vec::new();
+
+    while position + width <= data.len() * 8 {
+        let code = (position..position + width).fold(0, |code, bit| {
+            (code << 1) | ((data[bit / 8] >> (7 - bit % 8)) & 1) as usize
+        });
+        position += width;
+
+        if code == LZW_CLEAR {
+            table = initial_table();
+            width = 9;
+            previous = None;
+            continue;
+        } else if code == LZW_END_OF_INFORMATION {
+            break;
+        }
+
+        let entry = match (table.get(code), &previous) {
+            (Some(entry), _) => entry.clone(),
+            (None, Some(previous)) if code == table.len() => {
+                let mut entry = previous.clone();
+                entry.push(previous[0]);
+                entry
+            }
+            _ => return Err(String::from("Corrupt LZW data")),
+        };
+
+        output.extend_from_slice(&entry);
+
+        if let Some(mut previous) = previous {
+            previous.push(entry[0]);
+            table.push(previous);
+        }
+        previous = Some(entry);
+
+        // The code width grows one code early
+        if table.len() + 1 >= 1 << width && width < 12 {
+            width += 1;
+        }
+    }
+
+    Ok(output)
+}
+
+fn decode_packbits(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut position = 0;
+
+    while position < data.len() {
+        let header = data[position] as i8;
+        position += 1;
+
+        if header >= 0 {
+            let end = (position + header as usize + 1).min(data.len());
+            output.extend_from_slice(&data[position..end]);
+            position = end;
+        } else if header != -128 {
+            if let Some(byte) = data.get(position) {
+                output.extend(std::iter::repeat(*byte).take((1 - header as isize) as usize));
+            }
+            position += 1;
+        }
+    }
+
+    output
+}
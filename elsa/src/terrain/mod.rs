@@ -0,0 +1,444 @@
+use geo::{prelude::HaversineDistance, Point};
+use wasm_bindgen::prelude::*;
+
+mod ascii_grid;
+mod geotiff;
+mod projection;
+mod xyz;
+
+/// Largest number of cells a single grid may consist of, guards against sparse or malformed point lists
+const MAXIMUM_GRID_CELLS: usize = 100_000_000;
+
+/// Coordinate reference system in which the cells of a grid are laid out
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Crs {
+    /// Longitude and latitude in degrees (e.g. EPSG:4326)
+    Geographic,
+    /// Universal Transverse Mercator on the GRS80/WGS84 ellipsoid (e.g. EPSG:25832 used by DGM1 tiles)
+    Utm { zone: u8, north: bool },
+}
+
+impl Crs {
+    pub fn from_epsg(code: u32) -> Result<Self, String> {
+        match code {
+            4258 | 4326 => Ok(Crs::Geographic),
+            25801..=25860 => Ok(Crs::Utm {
+                zone: (code - 25800) as u8,
+                north: true,
+            }),
+            32601..=32660 => Ok(Crs::Utm {
+                zone: (code - 32600) as u8,
+                north: true,
+            }),
+            32701..=32760 => Ok(Crs::Utm {
+                zone: (code - 32700) as u8,
+                north: false,
+            }),
+            _ => Err(format!(
+                "Unsupported coordinate reference system EPSG:{}",
+                code
+            )),
+        }
+    }
+
+    /// Converts a geographic point into the coordinates of the reference system
+    fn project(&self, point: Point<f64>) -> (f64, f64) {
+        match self {
+            Crs::Geographic => (point.x(), point.y()),
+            Crs::Utm { zone, north } => projection::utm(point, *zone, *north),
+        }
+    }
+}
+
+/// Regular grid of elevations (in meters above MSL)
+#[derive(Debug)]
+pub(crate) struct ElevationGrid {
+    crs: Crs,
+    /// Coordinates of the center of the north-western cell
+    west: f64,
+    north: f64,
+    /// Distance between the centers of neighbouring cells
+    dx: f64,
+    dy: f64,
+    columns: usize,
+    rows: usize,
+    /// Row-major elevations beginning in the north-west, NaN where no data is available
+    values: Vec<f32>,
+}
+
+impl ElevationGrid {
+    fn new(
+        crs: Crs,
+        (west, north): (f64, f64),
+        (dx, dy): (f64, f64),
+        (columns, rows): (usize, usize),
+        values: Vec<f32>,
+    ) -> Result<Self, String> {
+        if columns == 0 || rows == 0 || !(dx > 0.0 && dy > 0.0) {
+            return Err(String::from("Grid has no extent"));
+        }
+
+        if values.len() != columns * rows {
+            return Err(format!(
+                "Grid of {}x{} cells contains {} values",
+                columns,
+                rows,
+                values.len()
+            ));
+        }
+
+        Ok(Self {
+            crs,
+            west,
+            north,
+            dx,
+            dy,
+            columns,
+            rows,
+            values,
+        })
+    }
+
+    fn value(&self, column: usize, row: usize) -> Option<f64> {
+        let value = self.values[row * self.columns + column];
+        (!value.is_nan()).then(|| value as f64)
+    }
+
+    /// Bilinearly interpolated elevation, cells without data are left out of the interpolation
+    fn elevation(&self, point: Point<f64>) -> Option<f64> {
+        let (x, y) = self.crs.project(point);
+        let column = (x - self.west) / self.dx;
+        let row = (self.north - y) / self.dy;
+
+        // Each cell covers half the spacing around its center
+        let (last_column, last_row) = ((self.columns - 1) as f64, (self.rows - 1) as f64);
+        if column < -0.5 || row < -0.5 || column > last_column + 0.5 || row > last_row + 0.5 {
+            return None;
+        }
+
+        let column = column.max(0.0).min(last_column);
+        let row = row.max(0.0).min(last_row);
+        let (left, top) = (column.floor() as usize, row.floor() as usize);
+        let (right, bottom) = (
+            (left + 1).min(self.columns - 1),
+            (top + 1).min(self.rows - 1),
+        );
+        let (fx, fy) = (column.fract(), row.fract());
+
+        let (weighted, weights) = [
+            (left, top, (1.0 - fx) * (1.0 - fy)),
+            (right, top, fx * (1.0 - fy)),
+            (left, bottom, (1.0 - fx) * fy),
+            (right, bottom, fx * fy),
+        ]
+        .iter()
+        .filter_map(|(column, row, weight)| self.value(*column, *row).map(|value| (value, *weight)))
+        .fold((0.0, 0.0), |(weighted, weights), (value, weight)| {
+            (weighted + value * weight, weights + weight)
+        });
+
+        (weights > 0.0).then(|| weighted / weights)
+    }
+}
+
+/// Digital elevation model assembled from any number of grids, e.g. individual tiles of a larger dataset
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct ElevationModel {
+    grids: Vec<ElevationGrid>,
+}
+
+impl ElevationModel {
+    /// Adds a grid given as lines of `x y z`, as is common for official datasets like DGM1
+    pub fn add_xyz(&mut self, source: &str, crs: Crs) -> Result<(), String> {
+        self.grids.push(xyz::parse_xyz(source, crs)?);
+        Ok(())
+    }
+
+    /// Adds a grid in the ESRI ASCII grid format (`.asc`)
+    pub fn add_ascii_grid(&mut self, source: &str, crs: Crs) -> Result<(), String> {
+        self.grids.push(ascii_grid::parse_ascii_grid(source, crs)?);
+        Ok(())
+    }
+
+    /// Adds the first band of a GeoTIFF, the reference system is read from its GeoKeys
+    pub fn add_geotiff(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.grids.push(geotiff::parse_geotiff(bytes)?);
+        Ok(())
+    }
+
+    /// Elevation (in meters above MSL) of the terrain at the given point, `None` if no grid covers it
+    pub fn elevation(&self, point: Point<f64>) -> Option<f64> {
+        self.grids.iter().find_map(|grid| grid.elevation(point))
+    }
+
+    /// Distance (in meters) along a line at which the terrain first rises above the altitude (in meters above MSL)
+    /// which the given function yields for each distance. The line is sampled at the given resolution and terrain
+    /// without data is assumed to be clear. Returns `None` if the terrain stays below the line.
+    pub fn obstruction<F>(&self, points: &[Point<f64>], resolution: f64, altitude: F) -> Option<f64>
+    where
+        F: Fn(f64) -> f64,
+    {
+        let mut travelled = 0.0;
+
+        for segment in points.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            let length = start.haversine_distance(&end);
+            let samples = (length / resolution).ceil().max(1.0) as usize;
+
+            for i in 0..samples {
+                let fraction = i as f64 / samples as f64;
+                let distance = travelled + length * fraction;
+                let point = start + (end - start) * fraction;
+
+                if let Some(elevation) = self.elevation(point) {
+                    if elevation > altitude(distance) {
+                        return Some(distance);
+                    }
+                }
+            }
+
+            travelled += length;
+        }
+
+        let end = *points.last()?;
+        self.elevation(end)
+            .filter(|elevation| *elevation > altitude(travelled))
+            .map(|_| travelled)
+    }
+}
+
+#[wasm_bindgen]
+impl ElevationModel {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a grid of `x y z` lines whose coordinates are given in the reference system with the EPSG code
+    #[wasm_bindgen(js_name = addXYZ)]
+    pub fn add_xyz_with_epsg(&mut self, source: String, epsg: u32) -> Result<(), JsValue> {
+        Ok(self
+            .add_xyz(&source, Crs::from_epsg(epsg)?)
+            .map_err(|e| e.to_string())?)
+    }
+
+    /// Adds an ESRI ASCII grid whose coordinates are given in the reference system with the EPSG code
+    #[wasm_bindgen(js_name = addASCIIGrid)]
+    pub fn add_ascii_grid_with_epsg(&mut self, source: String, epsg: u32) -> Result<(), JsValue> {
+        Ok(self
+            .add_ascii_grid(&source, Crs::from_epsg(epsg)?)
+            .map_err(|e| e.to_string())?)
+    }
+
+    #[wasm_bindgen(js_name = addGeoTIFF)]
+    pub fn add_geotiff_bytes(&mut self, bytes: Vec<u8>) -> Result<(), JsValue> {
+        Ok(self.add_geotiff(&bytes).map_err(|e| e.to_string())?)
+    }
+
+    /// Elevation (in meters above MSL) at the given coordinates
+    #[wasm_bindgen(js_name = elevationAt)]
+    pub fn elevation_at(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        self.elevation(Point::new(longitude, latitude))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian GeoTIFF of 4x2 unsigned 16 bit samples in a single LZW compressed strip. Its rows are
+    /// `100 100 100 100` and `100 100 120 140`, with pixels of 0.001° whose area begins at 10°E 53.5°N.
+    const LZW_GEOTIFF: [u8; 232] = [
+        0x49, 0x49, 0x2a, 0x00, 0x08, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x01, 0x03, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x02, 0x00, 0x00, 0x00, 0x02, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00,
+        0x00, 0x03, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x11, 0x01,
+        0x04, 0x00, 0x01, 0x00, 0x00, 0x00, 0xda, 0x00, 0x00, 0x00, 0x17, 0x01, 0x04, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x0e, 0x00, 0x00, 0x00, 0x0e, 0x83, 0x0c, 0x00, 0x03, 0x00, 0x00, 0x00,
+        0x7a, 0x00, 0x00, 0x00, 0x82, 0x84, 0x0c, 0x00, 0x06, 0x00, 0x00, 0x00, 0x92, 0x00, 0x00,
+        0x00, 0xaf, 0x87, 0x03, 0x00, 0x0c, 0x00, 0x00, 0x00, 0xc2, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0xfc, 0xa9, 0xf1, 0xd2, 0x4d, 0x62, 0x50, 0x3f, 0xfc, 0xa9, 0xf1, 0xd2, 0x4d,
+        0x62, 0x50, 0x3f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x24, 0x40, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0xc0, 0x4a, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x04, 0x00, 0x00, 0x01, 0x00, 0x02, 0x00,
+        0x01, 0x04, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x80, 0x19, 0x00, 0x10, 0x28, 0x24, 0x0e,
+        0x0c, 0x78, 0x00, 0x23, 0x00, 0x10, 0x10,
+    ];
+
+    fn assert_elevation(model: &ElevationModel, x: f64, y: f64, expected: Option<f64>) {
+        let elevation = model.elevation(Point::new(x, y));
+
+        assert!(
+            match (elevation, expected) {
+                (Some(elevation), Some(expected)) => (elevation - expected).abs() < 1e-2,
+                (elevation, expected) => elevation == expected,
+            },
+            "elevation at {} {} is {:?} instead of {:?}",
+            x,
+            y,
+            elevation,
+            expected
+        );
+    }
+
+    #[test]
+    fn lzw_geotiff() {
+        let mut model = ElevationModel::default();
+        model.add_geotiff(&LZW_GEOTIFF).unwrap();
+
+        assert_elevation(&model, 10.0005, 53.4995, Some(100.0));
+        assert_elevation(&model, 10.0025, 53.4985, Some(120.0));
+        assert_elevation(&model, 10.0035, 53.4985, Some(140.0));
+        assert_elevation(&model, 10.003, 53.4985, Some(130.0));
+        assert_elevation(&model, 10.005, 53.4985, None);
+    }
+
+    #[test]
+    fn ascii_grid_registration() {
+        let values = "10 20 30\n40 50 -9999\n";
+        let corner = format!(
+            "ncols 3\nnrows 2\nxllcorner 10.0\nyllcorner 53.5\ncellsize 0.01\nNODATA_value -9999\n{}",
+            values
+        );
+        let center = format!(
+            "ncols 3\nnrows 2\nxllcenter 10.005\nyllcenter 53.505\ncellsize 0.01\nNODATA_value -9999\n{}",
+            values
+        );
+
+        for source in [corner, center] {
+            let mut model = ElevationModel::default();
+            model.add_ascii_grid(&source, Crs::Geographic).unwrap();
+
+            assert_elevation(&model, 10.005, 53.515, Some(10.0));
+            assert_elevation(&model, 10.015, 53.505, Some(50.0));
+            assert_elevation(&model, 10.01, 53.515, Some(15.0));
+            // Cells without data are left out of the interpolation
+            assert_elevation(&model, 10.02, 53.505, Some(50.0));
+            assert_elevation(&model, 9.999, 53.515, None);
+        }
+    }
+
+    #[test]
+    fn utm_projection() {
+        // On the central meridian the northing is the scaled meridian arc of GRS80
+        let (easting, northing) = projection::utm(Point::new(9.0, 45.0), 32, true);
+        assert!((easting - 500_000.0).abs() < 1e-3 && (northing - 4_982_950.400).abs() < 1e-3);
+
+        // Samples of a plane around 10°E 53.5°N, which is at 566331.528E 5928359.088N in EPSG:25832
+        let source = "ncols 3\nnrows 3\nxllcenter 566330\nyllcenter 5928358\ncellsize 1\n\
+                      20 21 22\n10 11 12\n0 1 2\n";
+        let mut model = ElevationModel::default();
+        model
+            .add_ascii_grid(source, Crs::from_epsg(25832).unwrap())
+            .unwrap();
+
+        assert_elevation(&model, 10.0, 53.5, Some(1.528 + 10.0 * 1.088));
+    }
+
+    /// Little-endian GeoTIFF of 2x1 unsigned 16 bit samples `100 120` in a single uncompressed strip, stored
+    /// with horizontal differencing. Its pixels of 0.001° cover the area beginning at 10°E 53.5°N.
+    fn differenced_geotiff(samples_per_pixel: u16) -> Vec<u8> {
+        let mut bytes = b"II\x2a\x00\x08\x00\x00\x00".to_vec();
+        let entries: [(u16, u16, u32, u32); 10] = [
+            (256, 3, 1, 2),
+            (257, 3, 1, 1),
+            (258, 3, 1, 16),
+            (273, 4, 1, 134),
+            (277, 3, 1, samples_per_pixel as u32),
+            (279, 4, 1, 4),
+            (317, 3, 1, 2),
+            (33550, 12, 3, 138),
+            (33922, 12, 6, 162),
+            (34735, 3, 8, 210),
+        ];
+
+        bytes.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (tag, kind, count, value) in entries.iter() {
+            bytes.extend_from_slice(&tag.to_le_bytes());
+            bytes.extend_from_slice(&kind.to_le_bytes());
+            bytes.extend_from_slice(&count.to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+
+        for sample in [100u16, 20].iter() {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        for number in [0.001, 0.001, 0.0, 0.0, 0.0, 0.0, 10.0, 53.5, 0.0].iter() {
+            bytes.extend_from_slice(&f64::to_le_bytes(*number));
+        }
+        for key in [1u16, 1, 0, 1, 1024, 0, 1, 2].iter() {
+            bytes.extend_from_slice(&key.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn geotiff_samples_per_pixel() {
+        let mut model = ElevationModel::default();
+        model.add_geotiff(&differenced_geotiff(1)).unwrap();
+
+        assert_elevation(&model, 10.0005, 53.4995, Some(100.0));
+        assert_elevation(&model, 10.0015, 53.4995, Some(120.0));
+
+        assert_eq!(
+            ElevationModel::default().add_geotiff(&differenced_geotiff(0)),
+            Err(String::from("TIFF pixels have no samples"))
+        );
+    }
+
+    #[test]
+    fn xyz_grid() {
+        let mut model = ElevationModel::default();
+        model
+            .add_xyz(
+                "10.0 53.5 10\n10.01 53.5 20\n10.0,53.51,30\n10.01 53.51 40\n",
+                Crs::Geographic,
+            )
+            .unwrap();
+
+        assert_elevation(&model, 10.0, 53.5, Some(10.0));
+        assert_elevation(&model, 10.005, 53.505, Some(25.0));
+        assert_elevation(&model, 10.02, 53.5, None);
+    }
+
+    #[test]
+    fn non_finite_values_are_rejected() {
+        for source in [
+            "10.0 53.5 10\nNaN 53.5 20\n",
+            "10.0 53.5 10\n10.01 inf 20\n",
+            "10.0 53.5 10\n10.01 53.5 -infinity\n",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                ElevationModel::default().add_xyz(source, Crs::Geographic),
+                Err(String::from("Line 2: Values must be finite numbers"))
+            );
+        }
+
+        let grid = |cellsize: &str, values: &str| {
+            ElevationModel::default().add_ascii_grid(
+                &format!(
+                    "ncols 2\nnrows 1\nxllcorner 10.0\nyllcorner 53.5\ncellsize {}\n{}",
+                    cellsize, values
+                ),
+                Crs::Geographic,
+            )
+        };
+
+        assert!(grid("0.01", "10 20").is_ok());
+        assert_eq!(
+            grid("inf", "10 20"),
+            Err(String::from("Header cellsize: inf is not a finite number"))
+        );
+        assert_eq!(
+            grid("0.01", "10 NaN"),
+            Err(String::from("Value NaN is not a finite number"))
+        );
+    }
+}
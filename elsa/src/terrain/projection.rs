@@ -0,0 +1,50 @@
+use geo::Point;
+
+/// Semi-major axis of the GRS80 ellipsoid which ETRS89 is based on, differs from WGS84 by less than a millimeter
+const SEMI_MAJOR_AXIS: f64 = 6_378_137.0;
+const FLATTENING: f64 = 1.0 / 298.257_222_101;
+const SCALE_FACTOR: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.0;
+const FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+
+/// Projects a geographic point into the given UTM zone and returns its easting and northing in meters.
+/// Uses the series expansion by Krüger which is accurate to the millimeter within the zone.
+pub fn utm(point: Point<f64>, zone: u8, north: bool) -> (f64, f64) {
+    let n = FLATTENING / (2.0 - FLATTENING);
+    let rectifying_radius =
+        SEMI_MAJOR_AXIS / (1.0 + n) * (1.0 + n.powi(2) / 4.0 + n.powi(4) / 64.0);
+    let alpha = [
+        n / 2.0 - 2.0 * n.powi(2) / 3.0 + 5.0 * n.powi(3) / 16.0,
+        13.0 * n.powi(2) / 48.0 - 3.0 * n.powi(3) / 5.0,
+        61.0 * n.powi(3) / 240.0,
+    ];
+
+    let central_meridian = (zone as f64 * 6.0 - 183.0).to_radians();
+    let latitude = point.y().to_radians();
+    let longitude = point.x().to_radians() - central_meridian;
+
+    let eccentricity = 2.0 * n.sqrt() / (1.0 + n);
+    let t =
+        (latitude.sin().atanh() - eccentricity * (eccentricity * latitude.sin()).atanh()).sinh();
+    let xi = (t / longitude.cos()).atan();
+    let eta = (longitude.sin() / (1.0 + t.powi(2)).sqrt()).atanh();
+
+    let (easting, northing) =
+        alpha
+            .iter()
+            .enumerate()
+            .fold((eta, xi), |(easting, northing), (j, alpha)| {
+                let order = 2.0 * (j + 1) as f64;
+                (
+                    easting + alpha * (order * xi).cos() * (order * eta).sinh(),
+                    northing + alpha * (order * xi).sin() * (order * eta).cosh(),
+                )
+            });
+
+    let false_northing = if north { 0.0 } else { FALSE_NORTHING_SOUTH };
+
+    (
+        FALSE_EASTING + SCALE_FACTOR * rectifying_radius * easting,
+        false_northing + SCALE_FACTOR * rectifying_radius * northing,
+    )
+}
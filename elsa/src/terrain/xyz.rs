@@ -0,0 +1,75 @@
+use super::{Crs, ElevationGrid, MAXIMUM_GRID_CELLS};
+
+/// Parses lines of whitespace (or comma) separated `x y z` values which lie on a regular grid.
+/// The spacing is derived from the smallest distance between neighbouring coordinates.
+pub(super) fn parse_xyz(source: &str, crs: Crs) -> Result<ElevationGrid, String> {
+    let points = source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let values = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|value| !value.is_empty())
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+
+            match values[..] {
+                [x, y, z] if x.is_finite() && y.is_finite() && z.is_finite() => Ok((x, y, z)),
+                [_, _, _] => Err(format!("Line {}: Values must be finite numbers", index + 1)),
+                _ => Err(format!(
+                    "Line {}: Expected 3 values but found {}",
+                    index + 1,
+                    values.len()
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if points.is_empty() {
+        return Err(String::from("Grid contains no points"));
+    }
+
+    let xs = points.iter().map(|(x, _, _)| *x).collect::<Vec<_>>();
+    let ys = points.iter().map(|(_, y, _)| *y).collect::<Vec<_>>();
+    let (west, dx) = spacing(xs);
+    let (south, dy) = spacing(ys);
+
+    let east = points.iter().map(|(x, _, _)| *x).fold(f64::MIN, f64::max);
+    let north = points.iter().map(|(_, y, _)| *y).fold(f64::MIN, f64::max);
+    let columns = ((east - west) / dx).round() as usize + 1;
+    let rows = ((north - south) / dy).round() as usize + 1;
+
+    if columns.saturating_mul(rows) > MAXIMUM_GRID_CELLS {
+        return Err(format!(
+            "Grid of {}x{} cells is too large or not regularly spaced",
+            columns, rows
+        ));
+    }
+
+    let mut values = vec![f32::NAN; columns * rows];
+    for (x, y, z) in points {
+        let column = ((x - west) / dx).round() as usize;
+        let row = ((north - y) / dy).round() as usize;
+        values[row * columns + column] = z as f32;
+    }
+
+    ElevationGrid::new(crs, (west, north), (dx, dy), (columns, rows), values)
+}
+
+/// Smallest coordinate and the smallest distance between distinct coordinates, which is 1 if all coordinates are equal
+fn spacing(mut coordinates: Vec<f64>) -> (f64, f64) {
+    coordinates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    coordinates.dedup();
+
+    let spacing = coordinates
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .fold(f64::INFINITY, f64::min);
+
+    (
+        coordinates[0],
+        if spacing.is_finite() { spacing } else { 1.0 },
+    )
+}
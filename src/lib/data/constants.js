@@ -21,4 +21,12 @@ export const defaultPreferences = {
 
     eventLocationClassification: riskCategories[1],
     denselyCrowdedClassification: riskCategories[2],
+
+    // Survey ages (in days) beyond which locations are considered risky or unsafe, null ignores staleness
+    riskySurveyAge: null,
+    unsafeSurveyAge: null,
+
+    profileRays: 36,
+    profileMode: 'worstCase',
+    profileHeading: 0,
 };
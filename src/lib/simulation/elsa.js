@@ -1,9 +1,22 @@
 import { browser } from '$app/env';
 import Worker from './worker.js?worker';
+import { formatISO } from 'date-fns';
 import { feetToMeters } from '../units';
 
 const AIRCRAFT_CACHE = {};
 
+function serializeTime(time) {
+    return time ? formatISO(time) : null;
+}
+
+// Wind is either given as { direction, speed } or as a list of those with an additional altitude in feet
+function serializeWind(wind) {
+    if (!wind) return null;
+    if (!Array.isArray(wind)) return JSON.stringify(wind);
+
+    return JSON.stringify(wind.map(layer => ({ ...layer, altitude: feetToMeters(layer.altitude) })));
+}
+
 class ElsaWorker {
     constructor() {
         this.idCounter = 0;
@@ -44,17 +57,39 @@ class ElsaWorker {
         });
     }
 
-    reachabilityGeoJSON(preferences, aircraftID, altitudeInFeet) {
+    // Bounds are optional and given as [west, south, east, north] to skip locations outside the viewport
+    // Envelopes additionally cover the areas reachable with the worst and best heading
+    // Wind directions are given in degrees and speeds in knots, altitudes of a wind profile use the altitude reference
+    reachabilityGeoJSON(preferences, aircraftID, altitudeInFeet, time = null, bounds = null, envelopes = false, altitudeReference = 'AGL', wind = null) {
         const altitude = feetToMeters(altitudeInFeet);
 
         return this.submitRequest('REACHABILITY_GEOJSON', {
-            preferences, aircraftID, altitude
+            preferences, aircraftID, altitude, time: serializeTime(time), bounds, envelopes, altitudeReference, wind: serializeWind(wind)
         }).then(JSON.parse);
     }
 
-    locationLinesGeoJSON(preferences, aircraftID) {
+    // Same as reachabilityGeoJSON for a list of altitudes, resolves to one layer per altitude in the same order.
+    // Each layer carries its altitude (in meters) next to the polygons, so a slider can switch between them.
+    reachabilityGeoJSONForAltitudes(preferences, aircraftID, altitudesInFeet, time = null, bounds = null, envelopes = false, altitudeReference = 'AGL', wind = null) {
+        const altitudes = altitudesInFeet.map(altitude => feetToMeters(altitude));
+
+        return this.submitRequest('REACHABILITY_GEOJSON_FOR_ALTITUDES', {
+            preferences, aircraftID, altitudes, time: serializeTime(time), bounds, envelopes, altitudeReference, wind: serializeWind(wind)
+        }).then(JSON.parse);
+    }
+
+    // Heading is given in degrees and assumed to be flown at every point of the ranges
+    reachabilityGeoJSONForHeading(preferences, aircraftID, altitudeInFeet, heading, time = null, bounds = null, altitudeReference = 'AGL', wind = null) {
+        const altitude = feetToMeters(altitudeInFeet);
+
+        return this.submitRequest('REACHABILITY_GEOJSON_FOR_HEADING', {
+            preferences, aircraftID, altitude, heading, time: serializeTime(time), bounds, altitudeReference, wind: serializeWind(wind)
+        }).then(JSON.parse);
+    }
+
+    locationLinesGeoJSON(preferences, aircraftID, time = null) {
         return this.submitRequest('LOCATION_LINES_GEOJSON', {
-            preferences, aircraftID
+            preferences, aircraftID, time: serializeTime(time)
         }).then(JSON.parse);
     }
 
@@ -70,8 +105,10 @@ class ElsaWorker {
             .then(r => r !== null && r.distance < maximumDistance ? r.location : null);
     }
 
-    fetchLocation(preferences, locationID, aircraftID) {
-        return this.submitRequest('LOCATION_DATA', { preferences, locationID, aircraftID });
+    fetchLocation(preferences, locationID, aircraftID, time = null) {
+        return this.submitRequest('LOCATION_DATA', {
+            preferences, locationID, aircraftID, time: serializeTime(time)
+        });
     }
 
     fetchAircraftList() {
@@ -86,17 +123,76 @@ class ElsaWorker {
         return aircraft;
     }
 
-    landingOptions(preferences, latitude, longitude, heading, altitudeInFeet, aircraftID) {
+    // Altitude reference is either 'AGL' or 'MSL'
+    landingOptions(preferences, latitude, longitude, heading, altitudeInFeet, aircraftID, time = null, altitudeReference = 'AGL') {
         const altitude = feetToMeters(altitudeInFeet);
 
         return this.submitRequest('LANDING_OPTIONS', {
-            preferences, latitude, longitude, heading, altitude, aircraftID
+            preferences, latitude, longitude, heading, altitude, aircraftID, time: serializeTime(time), altitudeReference
         }).then(JSON.parse);
     }
 
+    resurveyReport(preferences, time = null) {
+        return this.submitRequest('RESURVEY_REPORT', {
+            preferences, time: serializeTime(time)
+        }).then(JSON.parse);
+    }
+
+    // Format is one of 'kml', 'gpx', or 'csv', the contents of the file are returned as a string
+    exportLocations(format, preferences, aircraftID, time = null) {
+        return this.submitRequest('EXPORT_LOCATIONS', {
+            format, preferences, aircraftID, time: serializeTime(time)
+        });
+    }
+
+    // Replaces the risk rules with the given YAML ruleset, passing null restores the default rules
+    setRules(yaml = null) {
+        return this.submitRequest('SET_RULES', { yaml });
+    }
+
+    // Currently active risk rules as YAML
+    fetchRules() {
+        return this.submitRequest('RULES');
+    }
+
+    // Loads elevation tiles to check glide paths for terrain clearance, passing null removes the terrain again.
+    // Each tile is given as { format: 'xyz' | 'asc' | 'tiff', data, epsg } where GeoTIFF data is an ArrayBuffer
+    // and the EPSG code of the coordinates is only required for the other formats (e.g. 25832 for DGM1 tiles).
+    setTerrain(tiles = null) {
+        return this.submitRequest('SET_TERRAIN', { tiles });
+    }
+
     takeoffProfile(aircraftID, distance) {
         return this.submitRequest('TAKEOFF_PROFILE', { aircraftID, distance }).then(JSON.parse);
     }
+
+    // Route is a GeoJSON LineString (or feature thereof) and the altitude profile consists of [distance, altitude] pairs in meters
+    routeCoverage(preferences, aircraftID, route, altitudeProfile, time = null, altitudeReference = 'AGL') {
+        return this.submitRequest('ROUTE_COVERAGE', {
+            preferences, aircraftID, route: JSON.stringify(route), altitudeProfile: JSON.stringify(altitudeProfile), time: serializeTime(time), altitudeReference
+        }).then(JSON.parse);
+    }
+
+    // Risk is either 'Safe', 'Risky' or 'Unsafe' and heading either 'WorstCase' or 'AlongTrack', returned altitudes are in meters
+    minimumAltitudeProfile(preferences, aircraftID, route, risk = 'Risky', heading = 'WorstCase', ceilingInFeet = 5000, time = null, altitudeReference = 'AGL') {
+        const ceiling = feetToMeters(ceilingInFeet);
+
+        return this.submitRequest('MINIMUM_ALTITUDE_PROFILE', {
+            preferences, aircraftID, route: JSON.stringify(route), risk, heading, ceiling, time: serializeTime(time), altitudeReference
+        }).then(JSON.parse);
+    }
+
+    // Grid of the minimum altitudes (in meters) from which a safe or risky location is reachable with the worst heading.
+    // Bounds are [west, south, east, north] and the resolution is the width of a cell in meters. Format is one of
+    // 'png' or 'tiff' (returned as a Uint8Array), 'svg' or 'asc' (returned as a string), the result also contains
+    // the bounds covered by the image as some overlays need to be stretched across them.
+    minimumAltitudeRaster(preferences, aircraftID, bounds, resolution, format = 'png', ceilingInFeet = 5000, time = null, altitudeReference = 'AGL') {
+        const ceiling = feetToMeters(ceilingInFeet);
+
+        return this.submitRequest('MINIMUM_ALTITUDE_RASTER', {
+            preferences, aircraftID, bounds, resolution, format, ceiling, time: serializeTime(time), altitudeReference
+        });
+    }
 }
 
 export const elsa = new ElsaWorker();
@@ -1,4 +1,4 @@
-import init, { Parser, Calculator, Preferences } from 'elsa';
+import init, { Parser, Calculator, Preferences, ElevationModel } from 'elsa';
 import { dev } from '$app/env';
 
 function parsePrefs(preferences) {
@@ -36,15 +36,27 @@ async function run() {
 
         switch (type) {
             case 'REACHABILITY_GEOJSON': {
-                let { preferences, aircraftID, altitude } = data;
+                let { preferences, aircraftID, altitude, time, bounds, envelopes, altitudeReference, wind } = data;
                 const aircraftInstance = aircrafts.get(aircraftID);
-                response = calculator.reachabilityGeoJSON(parsePrefs(preferences), locations, aircraftInstance, altitude);
+                response = calculator.reachabilityGeoJSON(parsePrefs(preferences), locations, aircraftInstance, altitude, time, bounds, envelopes, altitudeReference, wind);
+                break;
+            }
+            case 'REACHABILITY_GEOJSON_FOR_ALTITUDES': {
+                let { preferences, aircraftID, altitudes, time, bounds, envelopes, altitudeReference, wind } = data;
+                const aircraftInstance = aircrafts.get(aircraftID);
+                response = calculator.reachabilityGeoJSONForAltitudes(parsePrefs(preferences), locations, aircraftInstance, altitudes, time, bounds, envelopes, altitudeReference, wind);
+                break;
+            }
+            case 'REACHABILITY_GEOJSON_FOR_HEADING': {
+                let { preferences, aircraftID, altitude, heading, time, bounds, altitudeReference, wind } = data;
+                const aircraftInstance = aircrafts.get(aircraftID);
+                response = calculator.reachabilityGeoJSONForHeading(parsePrefs(preferences), locations, aircraftInstance, altitude, heading, time, bounds, altitudeReference, wind);
                 break;
             }
             case 'LOCATION_LINES_GEOJSON': {
-                let { preferences, aircraftID } = data;
+                let { preferences, aircraftID, time } = data;
                 const aircraft = aircrafts.get(aircraftID);
-                response = calculator.locationGeoJSON(parsePrefs(preferences), locations, aircraft);
+                response = calculator.locationGeoJSON(parsePrefs(preferences), locations, aircraft, time);
                 break;
             }
             case 'LOCATION_HITBOXES': {
@@ -58,10 +70,10 @@ async function run() {
                 break;
             }
             case 'LOCATION_DATA': {
-                let { preferences, locationID, aircraftID } = data;
+                let { preferences, locationID, aircraftID, time } = data;
                 const aircraft = aircrafts.get(aircraftID);
                 const location = locations.get(locationID);
-                response = serializeLocation(location, aircraft, calculator, parsePrefs(preferences));
+                response = serializeLocation(location, aircraft, calculator, parsePrefs(preferences), time);
                 break;
             }
             case 'AIRCRAFT_LIST': {
@@ -74,9 +86,38 @@ async function run() {
                 break;
             }
             case 'LANDING_OPTIONS': {
-                let { preferences, latitude, longitude, heading, altitude, aircraftID } = data;
+                let { preferences, latitude, longitude, heading, altitude, aircraftID, time, altitudeReference } = data;
+                const aircraft = aircrafts.get(aircraftID);
+                response = calculator.landingOptions(parsePrefs(preferences), latitude, longitude, heading, altitude, aircraft, locations, time, altitudeReference);
+                break;
+            }
+            case 'RESURVEY_REPORT': {
+                let { preferences, time } = data;
+                response = calculator.resurveyReport(parsePrefs(preferences), locations, time);
+                break;
+            }
+            case 'EXPORT_LOCATIONS': {
+                let { format, preferences, aircraftID, time } = data;
                 const aircraft = aircrafts.get(aircraftID);
-                response = calculator.landingOptions(parsePrefs(preferences), latitude, longitude, heading, altitude, aircraft, locations);
+
+                if (format === 'kml') response = locations.exportKML(calculator, parsePrefs(preferences), aircraft, time);
+                else if (format === 'gpx') response = locations.exportGPX(aircraft);
+                else if (format === 'csv') response = locations.exportCSV(aircraft);
+                else console.error('Received unknown export format', format);
+                break;
+            }
+            case 'SET_RULES': {
+                const { yaml } = data;
+                calculator.setRules(yaml);
+                break;
+            }
+            case 'RULES': {
+                response = calculator.rules();
+                break;
+            }
+            case 'SET_TERRAIN': {
+                const { tiles } = data;
+                calculator.setTerrain(tiles ? loadTerrain(tiles) : null);
                 break;
             }
             case 'TAKEOFF_PROFILE': {
@@ -86,6 +127,34 @@ async function run() {
                 console.log(response);
                 break;
             }
+            case 'ROUTE_COVERAGE': {
+                const { preferences, aircraftID, route, altitudeProfile, time, altitudeReference } = data;
+                const aircraft = aircrafts.get(aircraftID);
+                response = calculator.routeCoverage(parsePrefs(preferences), locations, aircraft, route, altitudeProfile, time, altitudeReference);
+                break;
+            }
+            case 'MINIMUM_ALTITUDE_PROFILE': {
+                const { preferences, aircraftID, route, risk, heading, ceiling, time, altitudeReference } = data;
+                const aircraft = aircrafts.get(aircraftID);
+                response = calculator.minimumAltitudeProfile(parsePrefs(preferences), locations, aircraft, route, risk, heading, ceiling, time, altitudeReference);
+                break;
+            }
+            case 'MINIMUM_ALTITUDE_RASTER': {
+                const { preferences, aircraftID, bounds, resolution, format, ceiling, time, altitudeReference } = data;
+                const aircraft = aircrafts.get(aircraftID);
+                const raster = calculator.minimumAltitudeRaster(parsePrefs(preferences), locations, aircraft, bounds, resolution, ceiling, time, altitudeReference);
+
+                let image = null;
+                if (format === 'png') image = raster.toPNG();
+                else if (format === 'tiff') image = raster.toGeoTIFF();
+                else if (format === 'svg') image = raster.toSVG();
+                else if (format === 'asc') image = raster.toASCIIGrid();
+                else console.error('Received unknown raster format', format);
+
+                response = { image, bounds: Array.from(raster.bounds), columns: raster.columns, rows: raster.rows };
+                raster.free();
+                break;
+            }
             default:
                 console.error('Received unknown worker request', type);
         }
@@ -103,9 +172,12 @@ run()
     .then(() => console.info('Worker ready.'))
     .catch(e => console.error('Worker failed:', e));
 
-function serializeLocation(location, aircraft, calculator, preferences) {
+function serializeLocation(location, aircraft, calculator, preferences, time) {
     if (!location) return null;
 
+    const risk = calculator.assessRisk(preferences, location, aircraft, time);
+    const ditching = risk.ditching;
+
     return {
         id: location.id,
         name: location.name,
@@ -122,11 +194,12 @@ function serializeLocation(location, aircraft, calculator, preferences) {
         surface: location.surface,
         humanPresence: location.humanPresence,
 
-        risk: calculator.assessRisk(preferences, location, aircraft).toJSON(),
+        risk: { ...risk.toJSON(), ditching: ditching ? ditching.toJSON() : null, reasons: JSON.parse(risk.reasons()) },
         landingHeadroom: location.landingHeadroom(aircraft),
 
         surveyDate: location.surveyDate,
-        remarks: location.remarks
+        remarks: location.remarks,
+        calendar: location.calendar
     };
 }
 
@@ -151,4 +224,17 @@ function serializeAircraft(aircraft) {
             totalDistance: aircraft.landing.totalDistance,
         },
     }
-}
\ No newline at end of file
+}
+
+function loadTerrain(tiles) {
+    const terrain = new ElevationModel();
+
+    for (const { format, data, epsg } of tiles) {
+        if (format === 'xyz') terrain.addXYZ(data, epsg);
+        else if (format === 'asc') terrain.addASCIIGrid(data, epsg);
+        else if (format === 'tiff') terrain.addGeoTIFF(new Uint8Array(data));
+        else throw new Error(`Unsupported terrain format ${format}`);
+    }
+
+    return terrain;
+}
@@ -20,5 +20,8 @@ aircraftID.subscribe(aircraft => { if (browser) localStorage.setItem(aircraftKey
 export const disclaimerSeen = writable((browser && (localStorage.getItem(disclaimerSeenKey) === disclaimerSeenValue)) || false);
 disclaimerSeen.subscribe(value => { if (browser) localStorage.setItem(disclaimerSeenKey, value === true ? disclaimerSeenValue : 'dontThinkSo') });
 
+// Planned time of the flight, `null` referring to the current time
+export const flightTime = writable(null);
+
 export const preferences = writable((browser && JSON.parse(localStorage.getItem(preferencesKey))) || defaultPreferences);
 preferences.subscribe(value => { if (browser) localStorage.setItem(preferencesKey, JSON.stringify(value)) });
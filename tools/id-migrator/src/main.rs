@@ -0,0 +1,71 @@
+use elsa::Location;
+use std::fs;
+
+const LOCATIONS_FILE: &str = "../../static/data/locations.yml";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Writes the hash based identifier currently in use into every location without an explicit `id`.
+    // This keeps existing links working while making it possible to correct coordinates later on.
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| LOCATIONS_FILE.to_string());
+
+    let source = fs::read_to_string(&path)?;
+    let locations: Vec<Location> = serde_yaml::from_str(&source)?;
+
+    // Every location starts with a top-level `- name:` line, commented out entries are skipped
+    let headers = source
+        .lines()
+        .filter(|line| line.starts_with("- name:"))
+        .count();
+
+    if headers != locations.len() {
+        return Err(format!(
+            "Found {} location headers but parsed {} locations, refusing to edit the file",
+            headers,
+            locations.len()
+        )
+        .into());
+    }
+
+    let mut pending = locations.iter();
+    let mut migrated = 0;
+    let mut output = String::with_capacity(source.len());
+
+    for line in source.lines() {
+        output.push_str(line);
+        output.push('\n');
+
+        if line.starts_with("- name:") {
+            let location = pending.next().unwrap();
+
+            if location.raw_id.is_none() {
+                output.push_str(&format!("  id: {}\n", location.hash_id()));
+                migrated += 1;
+                println!("{:>16} {}", location.hash_id(), location.name);
+            }
+        }
+    }
+
+    // Preserve the absence of a trailing newline
+    if !source.ends_with('\n') {
+        output.pop();
+    }
+
+    // Make sure that every identifier survived the round trip (e.g. was not read back as a number)
+    let migrated_locations: Vec<Location> = serde_yaml::from_str(&output)?;
+    for (before, after) in locations.iter().zip(migrated_locations.iter()) {
+        if after.id() != before.id() {
+            return Err(format!(
+                "Identifier of location '{}' changed during migration",
+                before.name
+            )
+            .into());
+        }
+    }
+
+    fs::write(&path, output)?;
+    println!("Migrated {} of {} locations", migrated, locations.len());
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use elsa::{import_geojson, import_kml, import_osm_pbf, import_osm_xml};
+use std::{fs, fs::File, path::Path};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Converts foreign geodata into location drafts which can be reviewed and appended to `locations.yml`.
+    // The drafts are written to stdout while the review report goes to stderr.
+    let path = std::env::args()
+        .nth(1)
+        .ok_or("Usage: location-importer <file.geojson|file.kml|file.osm|file.osm.pbf>")?;
+
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let import = match extension.as_str() {
+        "geojson" | "json" => import_geojson(&fs::read_to_string(&path)?)?,
+        "kml" => import_kml(&fs::read_to_string(&path)?)?,
+        "osm" | "xml" => import_osm_xml(&fs::read_to_string(&path)?)?,
+        "pbf" => import_osm_pbf(File::open(&path)?)?,
+        _ => return Err(format!("Unsupported file type: '{}'", extension).into()),
+    };
+
+    print!("{}", import.drafts_yaml()?);
+    eprint!("{}", import.report());
+
+    Ok(())
+}
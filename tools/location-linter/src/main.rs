@@ -0,0 +1,37 @@
+use elsa::{lint_locations, Severity};
+use std::{fs, process};
+
+const LOCATIONS_FILE: &str = "../../static/data/locations.yml";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| LOCATIONS_FILE.to_string());
+
+    let source = fs::read_to_string(&path)?;
+    let issues = lint_locations(&source);
+
+    for issue in issues.iter() {
+        match issue.line {
+            Some(line) => println!("{}:{}: {}", path, line, issue),
+            None => println!("{}: {}", path, issue),
+        }
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+
+    println!(
+        "Found {} errors and {} warnings",
+        errors,
+        issues.len() - errors
+    );
+
+    if errors > 0 {
+        process::exit(1);
+    }
+
+    Ok(())
+}